@@ -0,0 +1,48 @@
+//! 插入 0..5000 后按随机顺序全部删除，每删一个检查 len 是否减一，最后检查树是否为空，
+//! 出错时把操作序列缩小成可以粘贴进 tests/ 的测试。
+//!
+//! cargo run --release --example shuffle_delete [轮数，默认 20]
+
+use std::env;
+
+use rand::thread_rng;
+use rand::seq::SliceRandom;
+
+#[macro_use]
+extern crate timeit;
+
+use rb_tree::fuzz::{shrink, to_test, Op};
+use rb_tree::rbtree_mod::*;
+
+const N: i32 = 5000;
+
+fn main() {
+    let rounds: u32 = env::args().nth(1).and_then(|s| s.parse().ok()).unwrap_or(20);
+    let mut rng = thread_rng();
+    let t = timeit_loops!(1, {
+        'l: for round in 0..rounds {
+            let mut tree = RedBlackTree::new();
+            let keys: Vec<i32> = (0..N).collect();
+            for &i in &keys {
+                tree.add(i);
+            }
+            let mut order = keys.clone();
+            order.shuffle(&mut rng);
+            let mut count = tree.len();
+            for &i in &order {
+                tree.del(&i);
+                if tree.len() + 1 != count {
+                    let ops: Vec<Op> = keys.iter().map(|&k| Op::Add(k))
+                        .chain(order.iter().map(|&k| Op::Del(k)))
+                        .collect();
+                    println!("round {} failed deleting {}", round, i);
+                    println!("{}", to_test("shuffle_delete", &shrink(&ops)));
+                    break 'l;
+                }
+                count = tree.len();
+            }
+            assert!(tree.is_empty());
+        }
+    });
+    println!("{} rounds, {:.3}s", rounds, t);
+}
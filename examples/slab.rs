@@ -0,0 +1,108 @@
+//! 给 RawRedBlackTree 用的 slab 分配器：按块向系统申请，每块切成若干节点大小的槽，
+//! del 释放的槽串进空闲链表，下一次 add 优先复用，所以删一批再插一批不会再向系统申请内存。
+//!
+//! cargo run --example slab
+
+use std::alloc::{alloc, dealloc, Layout};
+use std::mem::{align_of, size_of};
+use std::ptr::NonNull;
+
+use rb_tree::rbtree_mod::{NodeAlloc, RawRedBlackTree};
+
+const SLOTS_PER_CHUNK: usize = 64;
+
+struct Slab {
+    // 第一次 allocate 时确定，之后的请求必须相同
+    slot: Option<Layout>,
+    chunks: Vec<NonNull<u8>>,
+    // 最后一块中还没用过的槽数
+    fresh: usize,
+    // del 释放的槽，槽的开头存下一个空闲槽
+    free: Option<NonNull<u8>>,
+    live: usize,
+    reused: usize
+}
+
+impl Slab {
+    fn new() -> Self {
+        Slab { slot: None, chunks: Vec::new(), fresh: 0, free: None, live: 0, reused: 0 }
+    }
+    fn chunk_layout(slot:Layout) -> Layout {
+        Layout::from_size_align(slot.size() * SLOTS_PER_CHUNK, slot.align()).unwrap()
+    }
+    // 从最后一块切一个新槽，用完了再申请一块
+    fn carve(&mut self, slot:Layout) -> Option<NonNull<u8>> {
+        if self.fresh == 0 {
+            self.chunks.push(NonNull::new(unsafe { alloc(Self::chunk_layout(slot)) })?);
+            self.fresh = SLOTS_PER_CHUNK;
+        }
+        let chunk = *self.chunks.last()?;
+        self.fresh -= 1;
+        Some(unsafe { chunk.add((SLOTS_PER_CHUNK - 1 - self.fresh) * slot.size()) })
+    }
+}
+
+unsafe impl NodeAlloc for Slab {
+    fn allocate(&mut self, layout:Layout) -> Option<NonNull<u8>> {
+        // 槽里要放得下空闲链表的指针
+        let layout = layout.pad_to_align();
+        if layout.size() < size_of::<usize>() || layout.align() < align_of::<usize>() {
+            return None;
+        }
+        let slot = *self.slot.get_or_insert(layout);
+        if slot != layout {
+            return None;
+        }
+        let p = match self.free {
+            Some(p) => {
+                self.free = unsafe { p.cast::<Option<NonNull<u8>>>().read() };
+                self.reused += 1;
+                p
+            },
+            None => self.carve(slot)?
+        };
+        self.live += 1;
+        Some(p)
+    }
+    unsafe fn deallocate(&mut self, ptr:NonNull<u8>, _layout:Layout) {
+        ptr.cast::<Option<NonNull<u8>>>().write(self.free);
+        self.free = Some(ptr);
+        self.live -= 1;
+    }
+}
+
+impl Drop for Slab {
+    fn drop(&mut self) {
+        let Some(slot) = self.slot else {
+            return;
+        };
+        for &chunk in &self.chunks {
+            unsafe { dealloc(chunk.as_ptr(), Self::chunk_layout(slot)) };
+        }
+    }
+}
+
+fn main() {
+    let mut slab = Slab::new();
+    {
+        // 借用 slab，树释放后还能查看统计
+        let mut tree = RawRedBlackTree::new_in(&mut slab);
+        for i in 0..1000u64 {
+            tree.add(i);
+        }
+        let chunks = tree.allocator().chunks.len();
+        println!("after 1000 adds: {} chunks", chunks);
+        for i in (0..1000u64).step_by(2) {
+            tree.del(&i);
+        }
+        println!("after 500 dels: {} live nodes", tree.allocator().live);
+        for i in 1000..1500u64 {
+            tree.add(i);
+        }
+        assert_eq!(tree.allocator().chunks.len(), chunks, "freed slots should be reused");
+        assert!(tree.check_tree());
+        println!("after 500 more adds: {} chunks, {} slots reused", tree.allocator().chunks.len(), tree.allocator().reused);
+    }
+    assert_eq!(slab.live, 0);
+    println!("tree dropped: {} live nodes", slab.live);
+}
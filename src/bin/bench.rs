@@ -0,0 +1,470 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::{BTreeSet, HashSet};
+use std::env;
+use std::hint::black_box;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+use std::time::{Duration, Instant};
+
+use rb_tree::fuzz::Rng;
+use rb_tree::rbtree_mod::{CompactRedBlackTree, RawRedBlackTree, RedBlackTree};
+
+const USAGE: &str = "usage: bench [--sizes N,N,..] [--repeat N] [--seed N] [--only NAME] [--csv]
+  --sizes 默认 1000,10000,100000
+  --repeat 每组重复次数，取最快的一次 (默认 3)
+  --only 只运行名字包含 NAME 的负载
+  --csv 输出 CSV，默认输出 markdown 表格
+计时的同时统计每个操作的分配次数和字节数";
+
+// 统计分配次数和字节数的全局分配器，realloc 也算一次分配
+struct Counting;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+static ALLOC_BYTES: AtomicUsize = AtomicUsize::new(0);
+static FREE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout:Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Relaxed);
+        ALLOC_BYTES.fetch_add(layout.size(), Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr:*mut u8, layout:Layout) {
+        FREE_BYTES.fetch_add(layout.size(), Relaxed);
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr:*mut u8, layout:Layout, new_size:usize) -> *mut u8 {
+        ALLOCS.fetch_add(1, Relaxed);
+        ALLOC_BYTES.fetch_add(new_size, Relaxed);
+        FREE_BYTES.fetch_add(layout.size(), Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// 一次计时区间内的耗时和分配情况
+#[derive(Clone, Copy)]
+struct Sample {
+    time: Duration,
+    allocs: usize,
+    bytes: usize,
+    // 区间结束时比开始时多占用的堆内存
+    live: isize
+}
+
+struct Meter {
+    start: Instant,
+    allocs: usize,
+    alloc_bytes: usize,
+    free_bytes: usize
+}
+
+impl Meter {
+    fn start() -> Self {
+        Meter {
+            allocs: ALLOCS.load(Relaxed),
+            alloc_bytes: ALLOC_BYTES.load(Relaxed),
+            free_bytes: FREE_BYTES.load(Relaxed),
+            start: Instant::now()
+        }
+    }
+    fn stop(self) -> Sample {
+        let time = self.start.elapsed();
+        let bytes = ALLOC_BYTES.load(Relaxed) - self.alloc_bytes;
+        let freed = FREE_BYTES.load(Relaxed) - self.free_bytes;
+        Sample { time, allocs: ALLOCS.load(Relaxed) - self.allocs, bytes, live: bytes as isize - freed as isize }
+    }
+}
+
+// 参与比较的集合，都只需要这几个操作
+trait Set: Default {
+    const NAME: &'static str;
+    fn insert(&mut self, k:u32);
+    fn remove(&mut self, k:u32);
+    fn contains(&self, k:u32) -> bool;
+    // 不支持范围查询时返回 None
+    fn range_count(&self, lo:u32, hi:u32) -> Option<usize>;
+}
+
+impl Set for RedBlackTree<u32> {
+    const NAME: &'static str = "RedBlackTree";
+    fn insert(&mut self, k:u32) {
+        self.add(k);
+    }
+    fn remove(&mut self, k:u32) {
+        self.del(&k);
+    }
+    fn contains(&self, k:u32) -> bool {
+        RedBlackTree::contains(self, &k)
+    }
+    fn range_count(&self, lo:u32, hi:u32) -> Option<usize> {
+        Some(self.range(lo..hi).count())
+    }
+}
+
+impl Set for CompactRedBlackTree<u32> {
+    const NAME: &'static str = "Compact";
+    fn insert(&mut self, k:u32) {
+        self.add(k);
+    }
+    fn remove(&mut self, k:u32) {
+        self.del(&k);
+    }
+    fn contains(&self, k:u32) -> bool {
+        CompactRedBlackTree::contains(self, &k)
+    }
+    fn range_count(&self, lo:u32, hi:u32) -> Option<usize> {
+        Some(self.range(lo..hi).count())
+    }
+}
+
+impl Set for RawRedBlackTree<u32> {
+    const NAME: &'static str = "Raw";
+    fn insert(&mut self, k:u32) {
+        self.add(k);
+    }
+    fn remove(&mut self, k:u32) {
+        self.del(&k);
+    }
+    fn contains(&self, k:u32) -> bool {
+        RawRedBlackTree::contains(self, &k)
+    }
+    fn range_count(&self, lo:u32, hi:u32) -> Option<usize> {
+        Some(self.range(lo..hi).count())
+    }
+}
+
+impl Set for BTreeSet<u32> {
+    const NAME: &'static str = "BTreeSet";
+    fn insert(&mut self, k:u32) {
+        BTreeSet::insert(self, k);
+    }
+    fn remove(&mut self, k:u32) {
+        BTreeSet::remove(self, &k);
+    }
+    fn contains(&self, k:u32) -> bool {
+        BTreeSet::contains(self, &k)
+    }
+    fn range_count(&self, lo:u32, hi:u32) -> Option<usize> {
+        Some(self.range(lo..hi).count())
+    }
+}
+
+impl Set for HashSet<u32> {
+    const NAME: &'static str = "HashSet";
+    fn insert(&mut self, k:u32) {
+        HashSet::insert(self, k);
+    }
+    fn remove(&mut self, k:u32) {
+        HashSet::remove(self, &k);
+    }
+    fn contains(&self, k:u32) -> bool {
+        HashSet::contains(self, &k)
+    }
+    fn range_count(&self, _lo:u32, _hi:u32) -> Option<usize> {
+        None
+    }
+}
+
+// 有序 Vec，二分查找后插入、删除
+#[derive(Default)]
+struct SortedVec(Vec<u32>);
+
+impl Set for SortedVec {
+    const NAME: &'static str = "sorted Vec";
+    fn insert(&mut self, k:u32) {
+        if let Err(i) = self.0.binary_search(&k) {
+            self.0.insert(i, k);
+        }
+    }
+    fn remove(&mut self, k:u32) {
+        if let Ok(i) = self.0.binary_search(&k) {
+            self.0.remove(i);
+        }
+    }
+    fn contains(&self, k:u32) -> bool {
+        self.0.binary_search(&k).is_ok()
+    }
+    fn range_count(&self, lo:u32, hi:u32) -> Option<usize> {
+        let start = self.0.partition_point(|&x| x < lo);
+        let end = self.0.partition_point(|&x| x < hi);
+        Some(end - start)
+    }
+}
+
+fn shuffled(n:u32, rng:&mut Rng) -> Vec<u32> {
+    let mut keys: Vec<u32> = (0..n).collect();
+    for i in (1..keys.len()).rev() {
+        let j = rng.below(i as u64 + 1) as usize;
+        keys.swap(i, j);
+    }
+    keys
+}
+
+// 0, n-1, 1, n-2, ... 每次插入都落在已有区间的两端
+fn zigzag(n:u32) -> Vec<u32> {
+    (0..n).map(|i| if i % 2 == 0 { i / 2 } else { n - 1 - i / 2 }).collect()
+}
+
+// 一个负载：准备数据 (不计时) 和执行 (计时)，返回操作次数
+enum Work {
+    Insert(Vec<u32>),
+    Delete { fill:Vec<u32>, keys:Vec<u32> },
+    Mixed { fill:Vec<u32>, ops:Vec<(u64, u32)> },
+    Range { fill:Vec<u32>, queries:Vec<(u32, u32)> }
+}
+
+struct Workload {
+    name: &'static str,
+    build: fn(u32, &mut Rng) -> Work
+}
+
+const WORKLOADS: &[Workload] = &[
+    Workload { name: "insert ascending", build: |n, _| Work::Insert((0..n).collect()) },
+    Workload { name: "insert descending", build: |n, _| Work::Insert((0..n).rev().collect()) },
+    Workload { name: "insert zigzag", build: |n, _| Work::Insert(zigzag(n)) },
+    Workload { name: "insert random", build: |n, rng| Work::Insert(shuffled(n, rng)) },
+    Workload {
+        name: "delete ascending",
+        build: |n, rng| Work::Delete { fill: shuffled(n, rng), keys: (0..n).collect() }
+    },
+    Workload {
+        name: "delete zigzag",
+        build: |n, rng| Work::Delete { fill: shuffled(n, rng), keys: zigzag(n) }
+    },
+    Workload {
+        name: "delete random",
+        build: |n, rng| Work::Delete { fill: shuffled(n, rng), keys: shuffled(n, rng) }
+    },
+    Workload {
+        // 一半的键预先插入，然后 50% 查找、25% 插入、25% 删除
+        name: "mixed 50/25/25",
+        build: |n, rng| {
+            let fill = shuffled(n, rng).into_iter().filter(|k| k % 2 == 0).collect();
+            let ops = (0..n).map(|_| (rng.below(4), rng.below(n as u64) as u32)).collect();
+            Work::Mixed { fill, ops }
+        }
+    },
+    Workload {
+        // 每次扫描 100 个键
+        name: "range scan 100",
+        build: |n, rng| {
+            let queries = (0..(n / 10).max(1)).map(|_| {
+                let lo = rng.below(n as u64) as u32;
+                (lo, lo.saturating_add(100))
+            }).collect();
+            Work::Range { fill: shuffled(n, rng), queries }
+        }
+    }
+];
+
+fn filled<S: Set>(keys:&[u32]) -> S {
+    let mut s = S::default();
+    for &k in keys {
+        s.insert(k);
+    }
+    s
+}
+
+// 返回操作次数和计时区间的统计，不支持的负载返回 None
+fn run<S: Set>(work:&Work) -> Option<(usize, Sample)> {
+    match work {
+        Work::Insert(keys) => {
+            let mut s = S::default();
+            let m = Meter::start();
+            for &k in keys {
+                s.insert(k);
+            }
+            let sample = m.stop();
+            black_box(&s);
+            Some((keys.len(), sample))
+        }
+        Work::Delete { fill, keys } => {
+            let mut s: S = filled(fill);
+            let m = Meter::start();
+            for &k in keys {
+                s.remove(k);
+            }
+            let sample = m.stop();
+            black_box(&s);
+            Some((keys.len(), sample))
+        }
+        Work::Mixed { fill, ops } => {
+            let mut s: S = filled(fill);
+            let mut hits = 0;
+            let m = Meter::start();
+            for &(kind, k) in ops {
+                match kind {
+                    0 | 1 => hits += s.contains(k) as usize,
+                    2 => s.insert(k),
+                    _ => s.remove(k)
+                }
+            }
+            let sample = m.stop();
+            black_box((&s, hits));
+            Some((ops.len(), sample))
+        }
+        Work::Range { fill, queries } => {
+            let s: S = filled(fill);
+            let mut total = 0;
+            let m = Meter::start();
+            for &(lo, hi) in queries {
+                total += s.range_count(lo, hi)?;
+            }
+            let sample = m.stop();
+            black_box(total);
+            Some((queries.len(), sample))
+        }
+    }
+}
+
+// 重复 repeat 次取最快的一次
+fn best<S: Set>(work:&Work, repeat:u32) -> Option<(usize, Sample)> {
+    (0..repeat).map(|_| run::<S>(work)).min_by_key(|r| r.map(|(_, s)| s.time))?
+}
+
+struct Row {
+    workload: &'static str,
+    size: u32,
+    collection: &'static str,
+    ops: usize,
+    sample: Sample
+}
+
+impl Row {
+    fn per_op(&self, x:f64) -> f64 {
+        x / self.ops.max(1) as f64
+    }
+    fn ns_per_op(&self) -> f64 {
+        self.per_op(self.sample.time.as_nanos() as f64)
+    }
+    fn allocs_per_op(&self) -> f64 {
+        self.per_op(self.sample.allocs as f64)
+    }
+    fn bytes_per_op(&self) -> f64 {
+        self.per_op(self.sample.bytes as f64)
+    }
+    fn live_per_op(&self) -> f64 {
+        self.per_op(self.sample.live as f64)
+    }
+}
+
+fn bench<S: Set>(rows:&mut Vec<Row>, workload:&Workload, size:u32, work:&Work, repeat:u32) {
+    if let Some((ops, sample)) = best::<S>(work, repeat) {
+        rows.push(Row { workload: workload.name, size, collection: S::NAME, ops, sample });
+    }
+}
+
+const COLLECTIONS: [&str; 6] = ["RedBlackTree", "Compact", "Raw", "BTreeSet", "HashSet", "sorted Vec"];
+
+fn print_csv(rows:&[Row]) {
+    println!("workload,size,collection,ops,total_ns,ns_per_op,allocs_per_op,bytes_per_op,live_bytes_per_op");
+    for r in rows {
+        println!("{},{},{},{},{},{:.1},{:.3},{:.1},{:.1}", r.workload, r.size, r.collection, r.ops,
+            r.sample.time.as_nanos(), r.ns_per_op(), r.allocs_per_op(), r.bytes_per_op(), r.live_per_op());
+    }
+}
+
+// 每行一个 (负载, 规模)，每列一个集合
+fn print_markdown(title:&str, rows:&[Row], metric:fn(&Row) -> f64, precision:usize) {
+    println!("### {}\n", title);
+    println!("| workload | size | {} | RedBlackTree / BTreeSet |", COLLECTIONS.join(" | "));
+    println!("|---|---:|{}---:|", "---:|".repeat(COLLECTIONS.len()));
+    let mut i = 0;
+    while i < rows.len() {
+        let (workload, size) = (rows[i].workload, rows[i].size);
+        let group: Vec<&Row> = rows[i..].iter()
+            .take_while(|r| r.workload == workload && r.size == size)
+            .collect();
+        i += group.len();
+        let value = |name| group.iter().find(|r| r.collection == name).map(|r| metric(r));
+        let cells: Vec<String> = COLLECTIONS.iter()
+            .map(|&name| value(name).map_or("-".to_string(), |v| format!("{:.*}", precision, v)))
+            .collect();
+        let ratio = match (value("RedBlackTree"), value("BTreeSet")) {
+            (Some(a), Some(b)) if b > 0.0 => format!("{:.2}x", a / b),
+            _ => "-".to_string()
+        };
+        println!("| {} | {} | {} | {} |", workload, size, cells.join(" | "), ratio);
+    }
+    println!();
+}
+
+struct Args {
+    sizes: Vec<u32>,
+    repeat: u32,
+    seed: u64,
+    only: Option<String>,
+    csv: bool
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args { sizes: vec![1000, 10000, 100000], repeat: 3, seed: 0, only: None, csv: false };
+    let mut it = env::args().skip(1);
+    while let Some(flag) = it.next() {
+        if flag == "--csv" {
+            args.csv = true;
+            continue;
+        }
+        let value = it.next().ok_or_else(|| format!("{} needs a value", flag))?;
+        let bad = |e| format!("bad value for {}: {}", flag, e);
+        match flag.as_str() {
+            "--sizes" => {
+                args.sizes = value.split(',').map(|s| s.trim().parse()).collect::<Result<_, _>>().map_err(bad)?;
+            }
+            "--repeat" => args.repeat = value.parse().map_err(bad)?,
+            "--seed" => args.seed = value.parse().map_err(bad)?,
+            "--only" => args.only = Some(value),
+            _ => return Err(format!("unknown flag {}", flag))
+        }
+    }
+    if args.repeat == 0 {
+        return Err("--repeat must be at least 1".to_string());
+    }
+    Ok(args)
+}
+
+fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if cfg!(debug_assertions) {
+        eprintln!("warning: debug build, use `cargo run --release --bin bench`");
+    }
+    let mut rows = vec![];
+    for workload in WORKLOADS {
+        if args.only.as_ref().is_some_and(|only| !workload.name.contains(only.as_str())) {
+            continue;
+        }
+        for &size in &args.sizes {
+            // 每个集合用同一份数据
+            let work = (workload.build)(size, &mut Rng::new(args.seed));
+            bench::<RedBlackTree<u32>>(&mut rows, workload, size, &work, args.repeat);
+            bench::<CompactRedBlackTree<u32>>(&mut rows, workload, size, &work, args.repeat);
+            bench::<RawRedBlackTree<u32>>(&mut rows, workload, size, &work, args.repeat);
+            bench::<BTreeSet<u32>>(&mut rows, workload, size, &work, args.repeat);
+            bench::<HashSet<u32>>(&mut rows, workload, size, &work, args.repeat);
+            bench::<SortedVec>(&mut rows, workload, size, &work, args.repeat);
+        }
+    }
+    if args.csv {
+        print_csv(&rows);
+        return;
+    }
+    print_markdown("ns per op", &rows, Row::ns_per_op, 1);
+    print_markdown("allocations per op", &rows, Row::allocs_per_op, 3);
+    print_markdown("heap bytes allocated per op", &rows, Row::bytes_per_op, 1);
+    // 插入负载结束时多占用的堆内存就是每个元素的内存占用
+    let inserts: Vec<Row> = rows.into_iter().filter(|r| r.workload.starts_with("insert")).collect();
+    if !inserts.is_empty() {
+        print_markdown("live heap bytes per element after insert", &inserts, Row::live_per_op, 1);
+    }
+    println!("RedBlackTree<u32>::memory_usage():\n{}\n", RedBlackTree::<u32>::new().memory_usage());
+    println!("CompactRedBlackTree<u32>::memory_usage():\n{}", CompactRedBlackTree::<u32>::new().memory_usage());
+}
@@ -0,0 +1,103 @@
+use std::env;
+use std::panic;
+use std::process;
+use std::time::Instant;
+
+use rb_tree::fuzz::{check_ops, exhaustive, exhaustive_shapes, gen_ops, shrink, to_test, Config, Op};
+
+const USAGE: &str = "usage: fuzz [--seed N] [--runs N] [--steps N] [--keys N]
+       fuzz --exhaustive N
+       fuzz --shapes N
+  --runs 0 (默认) 表示一直运行直到出错
+  --exhaustive N 穷举 0..N 交替插入、删除能到达的所有树
+  --shapes N 对节点数不超过 N 的每一棵红黑树尝试每个删除，N >= 15 时覆盖所有删除规则";
+
+struct Args {
+    seed: u64,
+    runs: u64,
+    config: Config,
+    exhaustive: Option<i32>,
+    shapes: Option<usize>
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut exhaustive = None;
+    let mut shapes = None;
+    let mut seed = 0;
+    let mut runs = 0;
+    let mut config = Config::default();
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{} needs a value", flag))?;
+        let bad = |e| format!("bad value for {}: {}", flag, e);
+        match flag.as_str() {
+            "--seed" => seed = value.parse().map_err(bad)?,
+            "--runs" => runs = value.parse().map_err(bad)?,
+            "--steps" => config.steps = value.parse().map_err(bad)?,
+            "--keys" => config.keys = value.parse().map_err(bad)?,
+            "--exhaustive" => exhaustive = Some(value.parse().map_err(bad)?),
+            "--shapes" => shapes = Some(value.parse().map_err(bad)?),
+            _ => return Err(format!("unknown flag {}", flag))
+        }
+    }
+    Ok(Args { seed, runs, config, exhaustive, shapes })
+}
+
+fn report(name:&str, ops:&[Op]) -> ! {
+    // 进程只剩缩小这一件事，可以关掉 panic 信息
+    panic::set_hook(Box::new(|_| {}));
+    let small = shrink(ops);
+    if let Err(f) = check_ops(&small) {
+        println!("shrunk to {} ops, fails at {}\n", small.len(), f);
+    }
+    print!("{}", to_test(name, &small));
+    process::exit(1);
+}
+
+fn main() {
+    let Args { seed: start, runs, config, exhaustive: n, shapes } = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    let t = Instant::now();
+    if let Some(max) = shapes {
+        match exhaustive_shapes(max) {
+            Ok(coverage) => println!("max {} ({:.1?})\n{}", max, t.elapsed(), coverage),
+            Err((tree, k)) => {
+                println!("del {} failed on\n{}", k, tree);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(n) = n {
+        for i in 1..=n {
+            match exhaustive(i) {
+                Ok(coverage) => println!("n = {} ({:.1?})\n{}\n", i, t.elapsed(), coverage),
+                Err(ops) => {
+                    println!("n = {} failed after {:?}", i, ops);
+                    report(&format!("exhaustive_{}", i), &ops);
+                }
+            }
+        }
+        return;
+    }
+    let mut seed = start;
+    while runs == 0 || seed - start < runs {
+        let ops = gen_ops(seed, config);
+        if let Err(f) = check_ops(&ops) {
+            println!("seed {} failed at {}", seed, f);
+            report(&format!("fuzz_seed_{}", seed), &ops);
+        }
+        seed += 1;
+        if (seed - start) % 100 == 0 {
+            println!("{} seeds ok ({:.1?})", seed - start, t.elapsed());
+        }
+    }
+    if (seed - start) % 100 != 0 {
+        println!("{} seeds ok ({:.1?})", seed - start, t.elapsed());
+    }
+}
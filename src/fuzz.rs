@@ -0,0 +1,408 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::rbtree_mod::{Color, RedBlackTree, Side};
+
+/// 对 `RedBlackTree` 和 `BTreeSet` 同时执行的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add(i32),
+    Del(i32),
+    Get(i32),
+    Range(i32, i32)
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Op::Add(k) => write!(f, "add {}", k),
+            Op::Del(k) => write!(f, "del {}", k),
+            Op::Get(k) => write!(f, "get {}", k),
+            Op::Range(a, b) => write!(f, "range {} {}", a, b)
+        }
+    }
+}
+
+/// 第 step 个操作之后两边结果不一致，或树不再满足红黑树性质
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub step: usize,
+    pub op: Op,
+    pub reason: String
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "step {} ({}): {}", self.step, self.op, self.reason)
+    }
+}
+
+// splitmix64，保证同一个种子在任何平台上生成相同的序列
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed:u64) -> Self {
+        Self(seed)
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+    // [0, n)
+    pub fn below(&mut self, n:u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    pub steps: usize,
+    // 键取自 [0, keys)，范围越小 add/del 碰撞越多
+    pub keys: i32
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            steps: 2000,
+            keys: 256
+        }
+    }
+}
+
+pub fn gen_ops(seed:u64, config:Config) -> Vec<Op> {
+    let mut rng = Rng::new(seed);
+    let keys = config.keys.max(1) as u64;
+    let key = |rng:&mut Rng| rng.below(keys) as i32;
+    (0..config.steps)
+        .map(|_| match rng.below(10) {
+            0..=3 => Op::Add(key(&mut rng)),
+            4..=6 => Op::Del(key(&mut rng)),
+            7..=8 => Op::Get(key(&mut rng)),
+            _ => {
+                let (a, b) = (key(&mut rng), key(&mut rng));
+                Op::Range(a.min(b), a.max(b))
+            }
+        })
+        .collect()
+}
+
+fn apply(tree:&mut RedBlackTree<i32>, model:&mut BTreeSet<i32>, op:Op) -> Result<(), String> {
+    match op {
+        Op::Add(k) => {
+            tree.add(k);
+            model.insert(k);
+        },
+        Op::Del(k) => {
+            let (got, expected) = (tree.remove(&k), model.remove(&k));
+            if got != expected {
+                return Err(format!("remove returned {}, expected {}", got, expected));
+            }
+        },
+        Op::Get(k) => {
+            let (got, expected) = (tree.get(&k).map(|x| *x.borrow().value()), model.get(&k).copied());
+            if got != expected {
+                return Err(format!("get returned {:?}, expected {:?}", got, expected));
+            }
+        },
+        Op::Range(a, b) => {
+            let got: Vec<i32> = tree.range(a..=b).map(|x| *x.borrow().value()).collect();
+            let expected: Vec<i32> = model.range(a..=b).copied().collect();
+            if got != expected {
+                return Err(format!("range returned {:?}, expected {:?}", got, expected));
+            }
+        }
+    }
+    if tree.len() != model.len() {
+        return Err(format!("len is {}, expected {}", tree.len(), model.len()));
+    }
+    tree.validate().map_err(|e| e.to_string())?;
+    if !tree.iter().map(|x| *x.borrow().value()).eq(model.iter().copied()) {
+        return Err("contents differ from BTreeSet".to_string());
+    }
+    Ok(())
+}
+
+/// 依次执行 ops，每一步后比较结果并检查红黑树性质，panic 也算作失败
+pub fn check_ops(ops:&[Op]) -> Result<(), Failure> {
+    let mut tree = RedBlackTree::new();
+    let mut model = BTreeSet::new();
+    for (step, &op) in ops.iter().enumerate() {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| apply(&mut tree, &mut model, op)));
+        let reason = match result {
+            Ok(Ok(())) => continue,
+            Ok(Err(reason)) => reason,
+            Err(e) => {
+                let msg = e.downcast_ref::<&str>().map(|s| s.to_string())
+                    .or_else(|| e.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                format!("panicked: {}", msg)
+            }
+        };
+        return Err(Failure { step, op, reason });
+    }
+    Ok(())
+}
+
+pub fn run_seed(seed:u64, config:Config) -> Result<(), Failure> {
+    check_ops(&gen_ops(seed, config))
+}
+
+/// ddmin：在 fails 仍为 true 的前提下尽量删掉操作
+pub fn shrink_by(ops:&[Op], mut fails:impl FnMut(&[Op]) -> bool) -> Vec<Op> {
+    let mut ops = ops.to_vec();
+    if !fails(&ops) {
+        return ops;
+    }
+    let mut n = 2;
+    while ops.len() >= 2 {
+        let chunk = ops.len().div_ceil(n);
+        let chunks: Vec<(usize, usize)> = (0..n)
+            .map(|i| (i * chunk, ((i + 1) * chunk).min(ops.len())))
+            .filter(|(start, end)| start < end)
+            .collect();
+        // 先试每一块，再试去掉每一块
+        if let Some(&(start, end)) = chunks.iter().find(|&&(start, end)| fails(&ops[start..end])) {
+            ops = ops[start..end].to_vec();
+            n = 2;
+            continue;
+        }
+        let rest = chunks.iter()
+            .map(|&(start, end)| ops[..start].iter().chain(&ops[end..]).copied().collect::<Vec<Op>>())
+            .find(|rest| fails(rest));
+        if let Some(rest) = rest {
+            ops = rest;
+            n = (n - 1).max(2);
+            continue;
+        }
+        if n >= ops.len() {
+            break;
+        }
+        n = (n * 2).min(ops.len());
+    }
+    ops
+}
+
+/// 把失败的操作序列缩小为仍然失败的最小序列。
+/// 不改动全局 panic hook，缩小期间的 panic 信息照常输出，需要静默时由调用方自己设置 hook
+pub fn shrink(ops:&[Op]) -> Vec<Op> {
+    match check_ops(ops) {
+        Err(f) => shrink_by(&ops[..=f.step], |ops| check_ops(ops).is_err()),
+        Ok(()) => ops.to_vec()
+    }
+}
+
+/// 生成可以直接粘贴进 tests/ 的测试函数
+pub fn to_test(name:&str, ops:&[Op]) -> String {
+    let mut body = String::new();
+    for (i, op) in ops.iter().enumerate() {
+        if i % 8 == 0 {
+            body.push_str("\n        ");
+        } else {
+            body.push(' ');
+        }
+        body.push_str(&format!("{:?},", op));
+    }
+    format!("#[test]
+fn {}() {{
+    use rb_tree::fuzz::{{check_ops, Op::*}};
+    let ops = [{}
+    ];
+    assert_eq!(check_ops(&ops), Ok(()));
+}}
+", name, body)
+}
+
+/// 穷举检查的覆盖情况，cases 以 "del 规则N" / "delete_fix 规则N" / "insert_fix ..." 为键
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    pub states: usize,
+    pub transitions: usize,
+    pub cases: BTreeMap<String, usize>
+}
+
+impl Coverage {
+    pub fn merge(&mut self, other:Coverage) {
+        self.states += other.states;
+        self.transitions += other.transitions;
+        for (case, count) in other.cases {
+            *self.cases.entry(case).or_insert(0) += count;
+        }
+    }
+    // 没有触发过的删除规则
+    pub fn missing(&self) -> Vec<String> {
+        let del = (1..=6).map(|i| format!("del 规则{}", i));
+        let fix = (1..=12).map(|i| format!("delete_fix 规则{}", i));
+        del.chain(fix).filter(|c| !self.cases.contains_key(c)).collect()
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} states, {} transitions", self.states, self.transitions)?;
+        for (case, count) in &self.cases {
+            writeln!(f, "  {:<28} {}", case, count)?;
+        }
+        let missing = self.missing();
+        if missing.is_empty() {
+            write!(f, "all delete cases covered")
+        } else {
+            write!(f, "not covered: {}", missing.join(", "))
+        }
+    }
+}
+
+// 形状 + 颜色 + 键，相同签名的树后续行为完全相同
+fn signature(tree:&RedBlackTree<i32>) -> Vec<(i32, Color, usize, Side)> {
+    tree.iter_preorder().map(|v| (*v.value(), v.color, v.depth, v.side)).collect()
+}
+
+// 规则标签只保留到编号，例如 "del 规则4 X黑 P红 B黑" -> "del 规则4"
+fn case_name(label:&str) -> String {
+    let mut words = label.split(' ');
+    match (words.next(), words.next()) {
+        (Some(op @ ("del" | "delete_fix")), Some(rule)) if rule.starts_with("规则") => format!("{} {}", op, rule),
+        _ => label.to_string()
+    }
+}
+
+/// 从空树出发，每一步插入 0..n 中不在树里的键或删除树里的键，遍历所有能到达的树。
+/// 先删后插得到的形状只靠插入得不到，所以插入和删除交替进行。相同状态只展开一次，
+/// 按广度优先展开，失败时返回能复现的最短操作序列
+pub fn exhaustive(n:i32) -> Result<Coverage, Vec<Op>> {
+    let mut coverage = Coverage::default();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    seen.insert(vec![]);
+    queue.push_back((RedBlackTree::new(), vec![]));
+    while let Some((tree, ops)) = queue.pop_front() {
+        coverage.states += 1;
+        for k in 0..n {
+            let op = if tree.contains(&k) { Op::Del(k) } else { Op::Add(k) };
+            let next = step(&tree, &ops, op, &mut coverage)?;
+            if seen.insert(signature(&next.0)) {
+                queue.push_back(next);
+            }
+        }
+    }
+    Ok(coverage)
+}
+
+// 只有颜色和结构的树，Nil 为空子树
+#[derive(Debug, Clone)]
+enum Shape {
+    Nil,
+    Node(Color, Box<Shape>, Box<Shape>)
+}
+
+impl Shape {
+    fn size(&self) -> usize {
+        match self {
+            Shape::Nil => 0,
+            Shape::Node(_, l, r) => 1 + l.size() + r.size()
+        }
+    }
+    // 先序的 (键, 颜色, 深度, 方向)，键按中序从 0 编号
+    fn preorder(&self, first:i32, depth:usize, side:Side, out:&mut Vec<(i32, Color, usize, Side)>) {
+        if let Shape::Node(color, l, r) = self {
+            let key = first + l.size() as i32;
+            out.push((key, *color, depth, side));
+            l.preorder(first, depth + 1, Side::Left, out);
+            r.preorder(key + 1, depth + 1, Side::Right, out);
+        }
+    }
+}
+
+// 黑高为 h (空子树为 0，红节点不计)、节点数不超过 max 的子树，根为黑或为空
+fn black_shapes(h:usize, max:usize) -> Vec<Shape> {
+    if h == 0 {
+        return vec![Shape::Nil];
+    }
+    if max == 0 {
+        return vec![];
+    }
+    let mut out = vec![];
+    for l in any_shapes(h - 1, max - 1) {
+        for r in any_shapes(h - 1, max - 1 - l.size()) {
+            out.push(Shape::Node(Color::Black, Box::new(l.clone()), Box::new(r)));
+        }
+    }
+    out
+}
+
+// 根可以为红，红节点的子节点必须为黑
+fn any_shapes(h:usize, max:usize) -> Vec<Shape> {
+    let mut out = black_shapes(h, max);
+    if max > 0 {
+        for l in black_shapes(h, max - 1) {
+            for r in black_shapes(h, max - 1 - l.size()) {
+                out.push(Shape::Node(Color::Red, Box::new(l.clone()), Box::new(r)));
+            }
+        }
+    }
+    out
+}
+
+/// 节点数不超过 max 的所有合法红黑树 (每一种形状和染色)，键为 0..len
+pub fn all_trees(max:usize) -> Vec<RedBlackTree<i32>> {
+    let mut trees = vec![];
+    // 黑高为 h 的树至少有 2^h - 1 个节点
+    for h in (1..).take_while(|&h| (1 << h) - 1 <= max) {
+        for shape in black_shapes(h, max) {
+            let mut nodes = vec![];
+            shape.preorder(0, 0, Side::Root, &mut nodes);
+            trees.push(RedBlackTree::from_preorder(nodes));
+        }
+    }
+    trees
+}
+
+/// 对 all_trees(max) 的每一棵树尝试删除每个键，每一步都检查性质。
+/// 插入删除交替能到达的状态随键数指数增长，直接构造的树用少得多的状态覆盖 delete_fix 需要的深层形状。
+/// 失败时返回出错前的树 (to_ascii) 和删除的键
+pub fn exhaustive_shapes(max:usize) -> Result<Coverage, (String, i32)> {
+    let mut coverage = Coverage::default();
+    for tree in all_trees(max) {
+        coverage.states += 1;
+        for k in 0..tree.len() as i32 {
+            if step(&tree, &[], Op::Del(k), &mut coverage).is_err() {
+                return Err((tree.frame().to_ascii(), k));
+            }
+        }
+    }
+    Ok(coverage)
+}
+
+fn step(tree:&RedBlackTree<i32>, ops:&[Op], op:Op, coverage:&mut Coverage) -> Result<(RedBlackTree<i32>, Vec<Op>), Vec<Op>> {
+    let mut next = tree.clone();
+    let mut ops = ops.to_vec();
+    ops.push(op);
+    let mut expected: BTreeSet<i32> = tree.iter().map(|x| *x.borrow().value()).collect();
+    next.start_trace();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| match op {
+        Op::Add(k) => {
+            next.add(k);
+            expected.insert(k);
+        },
+        Op::Del(k) => {
+            next.del(&k);
+            expected.remove(&k);
+        },
+        Op::Get(_) | Op::Range(..) => {}
+    }));
+    if result.is_err() {
+        return Err(ops);
+    }
+    coverage.transitions += 1;
+    for s in next.take_trace().map(|t| t.steps).unwrap_or_default().iter().skip(1) {
+        *coverage.cases.entry(case_name(&s.label)).or_insert(0) += 1;
+    }
+    if next.validate().is_err() || !next.iter().map(|x| *x.borrow().value()).eq(expected.iter().copied()) {
+        return Err(ops);
+    }
+    Ok((next, ops))
+}
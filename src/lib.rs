@@ -1,758 +1,391 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+// 依赖 std 的部分：panic 捕获和 I/O
+#[cfg(feature = "std")]
+pub mod fuzz;
+#[cfg(feature = "std")]
+pub mod oplog;
+
 pub mod rbtree_mod {
-    use std::cell::RefCell;
-    use std::rc::{Rc, Weak};
-    #[derive(Debug, Clone, PartialEq)]
+    use core::borrow::Borrow;
+    use core::cell::RefCell;
+    use core::cmp::Ordering;
+    use core::ops::RangeBounds;
+    use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+    use alloc::rc::{Rc, Weak};
+    use alloc::boxed::Box;
+
+    mod balance;
+    mod bulk;
+    mod compact;
+    mod compare;
+    mod counters;
+    mod fixed;
+    mod handle;
+    mod hooks;
+    mod intrusive;
+    mod iter;
+    mod memory;
+    mod node_alloc;
+    mod ops;
+    mod raw;
+    mod stats;
+    mod trace;
+    mod traits;
+    mod traverse;
+    mod validate;
+    pub use bulk::{Drain, ExtractIf};
+    pub use compact::{CompactIter, CompactRedBlackTree};
+    pub use compare::*;
+    pub use counters::CounterSnapshot;
+    use counters::Counters;
+    pub use fixed::{CapacityFull, StaticIter, StaticRedBlackTree};
+    pub use handle::Handle;
+    pub use intrusive::{IntrusiveIter, IntrusiveRedBlackTree, Link, Linked};
+    pub use iter::Iter;
+    pub use raw::{RawIter, RawRedBlackTree};
+    pub use memory::MemoryUsage;
+    pub use node_alloc::{Global, NodeAlloc};
+    pub use stats::TreeStats;
+    pub use trace::{Frame, FrameNode, Rotation, Trace, TraceEvent, TraceStep};
+    use hooks::Hooks;
+    use trace::Tracer;
+    pub use validate::InvariantError;
+    pub use traverse::{LevelOrder, NodeView, PostOrder, PreOrder, Side};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub enum Color {
         Red,
         Black
     }
 
+    type NodeRef<K> = Rc<RefCell<Node<K>>>;
+
     #[derive(Debug, Clone)]
-    pub struct Node {
-        value: i32,
+    pub struct Node<K> {
+        // drain、extract_if 取出键后为 None，节点可能还被句柄持有
+        value: Option<K>,
         color: Color,
-        left: Option<Rc<RefCell<Node>>>,
-        right: Option<Rc<RefCell<Node>>>,
-        parent: Option<Weak<RefCell<Node>>>
+        left: Option<NodeRef<K>>,
+        right: Option<NodeRef<K>>,
+        parent: Option<Weak<RefCell<Node<K>>>>,
+        // 所在树的编号，0 表示已经不在树中
+        owner: usize
     }
 
-    impl Node {
-        pub fn new(value:i32, color:Color, parent:Option<Weak<RefCell<Node>>>) -> Self {
+    impl<K> Node<K> {
+        pub fn new(value:K, color:Color, parent:Option<Weak<RefCell<Node<K>>>>) -> Self {
+            debug_assert!(parent.as_ref().is_none_or(|p| p.strong_count() > 0), "parent node has been dropped");
             Self {
-                value: value,
-                color: color,
+                value: Some(value),
+                color,
                 left: None,
                 right: None,
-                parent: parent
+                parent,
+                owner: 0
             }
         }
+        /// 键已被 drain 或 extract_if 取出时 panic
+        pub fn value(&self) -> &K {
+            self.value.as_ref().expect("key was moved out by drain or extract_if")
+        }
+        pub fn color(&self) -> Color {
+            self.color
+        }
+        // 父节点总是比子节点活得久，Weak 失效说明链接已经坏了
+        fn parent_node(&self) -> Option<NodeRef<K>> {
+            let p = self.parent.as_ref()?.upgrade();
+            debug_assert!(p.is_some(), "parent node has been dropped");
+            p
+        }
     }
 
-    impl PartialEq for Node {
+    impl<K: PartialEq> PartialEq for Node<K> {
         fn eq(&self, other: &Self) -> bool {
             self.value == other.value
         }
     }
 
-    impl PartialOrd for Node {
-        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    impl<K: PartialOrd> PartialOrd for Node<K> {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
             self.value.partial_cmp(&other.value)
         }
     }
 
-    #[derive(Debug, Clone)]
-    pub struct RedBlackTree {
-        root:Option<Rc<RefCell<Node>>>,
-        len:usize
+    // 节点身份按指针判断，不能用值比较
+    fn is_same<K>(a:&Option<NodeRef<K>>, b:&NodeRef<K>) -> bool {
+        match a {
+            Some(a) => Rc::ptr_eq(a, b),
+            None => false
+        }
     }
-    impl RedBlackTree {
-        pub fn new() -> Self {
-            Self {
-                root: None,
-                len: 0
-            }
+
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+    // 树的编号，从 1 开始不重复。节点记下所在树的编号，O(1) 判断归属
+    #[cfg(target_has_atomic = "ptr")]
+    fn next_tree_id() -> usize {
+        NEXT_ID.fetch_add(1, Relaxed)
+    }
+
+    // 没有原子读改写的目标 (如 thumbv6m) 只有原子 load/store。这类芯片是单核，
+    // 只有在中断里同时建树时编号才可能重复
+    #[cfg(not(target_has_atomic = "ptr"))]
+    fn next_tree_id() -> usize {
+        let id = NEXT_ID.load(Relaxed);
+        NEXT_ID.store(id + 1, Relaxed);
+        id
+    }
+
+    // 平衡算法中的节点，相等按指针判断
+    struct NodePtr<K>(NodeRef<K>);
+
+    impl<K> Clone for NodePtr<K> {
+        fn clone(&self) -> Self {
+            NodePtr(self.0.clone())
         }
-        fn get_parent(&self, x:&Rc<RefCell<Node>>) -> Option<Rc<RefCell<Node>>> {
-            match x.as_ref().borrow().parent.clone() {
-                None => None,
-                Some(x) => {
-                    x.upgrade()
-                }
-            }
+    }
+
+    impl<K> PartialEq for NodePtr<K> {
+        fn eq(&self, other:&Self) -> bool {
+            Rc::ptr_eq(&self.0, &other.0)
         }
-        fn get_brother(&self, x:&Rc<RefCell<Node>>) -> Option<Rc<RefCell<Node>>> {
-            match self.get_parent(x) {
-                None => None,
-                Some(p) => {
-                    let p_node = p.as_ref().borrow();
-                    if p_node.left == Some(x.clone()) {
-                        p_node.right.clone()
-                    } else {
-                        p_node.left.clone()
-                    }
-                }
-            }
+    }
+
+    impl<K> Eq for NodePtr<K> {}
+
+    pub struct RedBlackTree<K, C = Natural> {
+        root:Option<NodeRef<K>>,
+        len:usize,
+        id:usize,
+        compare:C,
+        counters:Counters,
+        tracer:Option<Box<Tracer<K>>>,
+        hooks:Option<Box<Hooks<K>>>
+    }
+    // 计数、trace 和回调都挂在平衡算法的通知上
+    impl<K, C> balance::Links for RedBlackTree<K, C> {
+        type Ptr = NodePtr<K>;
+        fn root(&self) -> Option<NodePtr<K>> {
+            self.root.clone().map(NodePtr)
         }
-        fn is_left_node(&self, x:&Rc<RefCell<Node>>) -> bool {
-            match self.get_parent(x) {
-                None => false,
-                Some(p) => {
-                    p.as_ref().borrow().left == Some(x.clone())
-                }
-            }
+        fn set_root(&mut self, x:Option<NodePtr<K>>) {
+            self.root = x.map(|x| x.0);
         }
-        fn is_right_node(&self, x:&Rc<RefCell<Node>>) -> bool {
-            match self.get_parent(x) {
-                None => false,
-                Some(p) => {
-                    p.as_ref().borrow().right == Some(x.clone())
-                }
-            }
+        fn left(&self, x:&NodePtr<K>) -> Option<NodePtr<K>> {
+            x.0.as_ref().borrow().left.clone().map(NodePtr)
         }
-        fn swap_value(&self, a:&Rc<RefCell<Node>>, b:&Rc<RefCell<Node>>) {
-            let mut a_write = a.as_ref().borrow_mut();
-            let mut b_write = b.as_ref().borrow_mut();
-            (b_write.value, a_write.value) = (a_write.value, b_write.value);
-        }
-        fn set_color(&self, x:&Rc<RefCell<Node>>, color:Color) {
-            let mut x_write = x.as_ref().borrow_mut();
-            x_write.color = color.clone();
-        }
-        fn drop_node(&mut self, x:Rc<RefCell<Node>>) {
-            if let Some(p) = self.get_parent(&x) {
-                let mut p_write = p.as_ref().borrow_mut();
-                if p_write.left == Some(x.clone()) {
-                    p_write.left = None;
-                } else {
-                    p_write.right = None;
-                }
-            }
-            if self.root == Some(x.clone()) {
-                self.root = None;
-            }
-            drop(x);
-            self.len -= 1;
+        fn right(&self, x:&NodePtr<K>) -> Option<NodePtr<K>> {
+            x.0.as_ref().borrow().right.clone().map(NodePtr)
         }
-        fn insert_search(&self, value:i32, cmp: bool) -> Option<Rc<RefCell<Node>>> {
-            let mut pv = self.root.clone();
-            while let Some(temp) = pv.clone() {
-                let pv_node = temp.as_ref().borrow();
-                if value < pv_node.value {
-                    if pv_node.left.is_some() {
-                        pv = pv_node.left.clone();
-                        continue;
-                    }
-                } else if value > pv_node.value {
-                    if pv_node.right.is_some() {
-                        pv = pv_node.right.clone();
-                        continue;
-                    } 
-                } else if cmp && value == pv_node.value {
-                    return pv;
-                }
-                break;
-            }
-            if cmp {
-                None
-            } else {
-                pv
-            }
+        fn parent(&self, x:&NodePtr<K>) -> Option<NodePtr<K>> {
+            x.0.as_ref().borrow().parent_node().map(NodePtr)
         }
-        fn search_max_node(&self, x:Option<Rc<RefCell<Node>>>) -> Option<Rc<RefCell<Node>>> {
-            let mut x = x;
-            while let Some(n) = x.clone() {
-                let n_node = n.as_ref().borrow();
-                if n_node.right.is_some() {
-                    x = n_node.right.clone();
-                }  else {
-                    break;
-                }
-            }
-            x
+        fn color(&self, x:&NodePtr<K>) -> Color {
+            x.0.as_ref().borrow().color
         }
-        fn search_min_node(&self, x:Option<Rc<RefCell<Node>>>) -> Option<Rc<RefCell<Node>>> {
-            let mut x = x;
-            while let Some(n) = x.clone() {
-                let n_node = n.as_ref().borrow();
-                if n_node.left.is_some() {
-                    x = n_node.left.clone();
-                }  else {
-                    break;
-                }
-            }
-            x
+        fn set_left(&mut self, x:&NodePtr<K>, c:Option<NodePtr<K>>) {
+            x.0.as_ref().borrow_mut().left = c.map(|c| c.0);
+        }
+        fn set_right(&mut self, x:&NodePtr<K>, c:Option<NodePtr<K>>) {
+            x.0.as_ref().borrow_mut().right = c.map(|c| c.0);
         }
-        fn delete_search(&self, x:&Rc<RefCell<Node>>) -> Option<Rc<RefCell<Node>>> {
-            let x_node = x.as_ref().borrow();
-            if let Some(x) = x_node.right.clone() {
-                self.search_min_node(Some(x))
-            } else if let Some(x) = x_node.left.clone() {
-                self.search_max_node(Some(x))
-            } else {
-                None
+        fn set_parent(&mut self, x:&NodePtr<K>, p:Option<NodePtr<K>>) {
+            x.0.as_ref().borrow_mut().parent = p.map(|p| Rc::downgrade(&p.0));
+        }
+        fn set_color(&mut self, x:&NodePtr<K>, color:Color) {
+            x.0.as_ref().borrow_mut().color = color;
+        }
+        fn case(&mut self, case:balance::Case) {
+            match case {
+                balance::Case::InsertFixLoop => {
+                    self.counters.bump(|s| s.insert_fix_loop += 1);
+                    return;
+                },
+                balance::Case::Del(n) => self.counters.bump(|s| s.del_case[n - 1] += 1),
+                balance::Case::DeleteFix(n) => self.counters.bump(|s| s.delete_fix_case[n - 1] += 1),
+                _ => {}
             }
+            self.trace_case(|| case.label());
         }
-        fn root_fix(&self) {
-            if let Some(root) = self.root.clone() {
-                let mut root_write = root.as_ref().borrow_mut();
-                if root_write.color != Color::Black {
-                    root_write.color = Color::Black;
-                }
+        fn before_rotate(&mut self, kind:Rotation, x:&NodePtr<K>) {
+            match kind {
+                Rotation::Left => self.counters.bump(|s| s.left_rotate += 1),
+                Rotation::Right => self.counters.bump(|s| s.right_rotate += 1)
             }
+            self.trace_rotate(kind, &x.0);
+            self.notify_rotate(kind, &x.0);
         }
-        fn left_rotate(&mut self, x:&Rc<RefCell<Node>>) {
-            if let Some(p) = self.get_parent(x) {
-                let x_node = x.as_ref().borrow().clone();
-                let p_node = p.as_ref().borrow().clone();
-                if p_node.right == Some(x.clone()) {
-                    if let Some(g) = self.get_parent(&p) {
-                        let mut g_node = g.as_ref().borrow_mut();
-                        if g_node.left == Some(p.clone()) {
-                            g_node.left = Some(x.clone());
-                        } else {
-                            g_node.right = Some(x.clone());
-                        }
-                    }
-                    {
-                        let mut x_write = x.as_ref().borrow_mut();
-                        let mut p_write = p.as_ref().borrow_mut();
-                        if let Some(x_left) = x_node.left.clone() {
-                            let mut x_left_write = x_left.as_ref().borrow_mut();
-                            x_left_write.parent = Some(Rc::downgrade(&p));
-                        }
-                        p_write.right = x_node.left.clone();
-                        p_write.parent = Some(Rc::downgrade(x));
-                        x_write.left = Some(p.clone());
-                        x_write.parent = p_node.parent.clone();
-                    }
-                    if self.root == Some(p) {
-                        self.root = Some(x.clone());
-                    }
-                }
+        fn before_recolor(&mut self, x:&NodePtr<K>, from:Color, to:Color) {
+            self.counters.bump(|s| s.recolor += 1);
+            self.trace_recolor(&x.0, from, to);
+            self.notify_recolor(&x.0, from, to);
+        }
+        fn before_swap(&mut self, a:&NodePtr<K>, b:&NodePtr<K>) {
+            self.trace_swap(&a.0, &b.0);
+        }
+        fn before_unlink(&mut self, x:&NodePtr<K>) {
+            self.trace_remove(&x.0);
+            self.notify_remove(&x.0);
+        }
+    }
+    impl<K, C> balance::Keys for RedBlackTree<K, C> {
+        type Key = K;
+        type Compare = C;
+        fn with_key<R>(&self, x:&NodePtr<K>, f:impl FnOnce(&K) -> R) -> R {
+            f(x.0.as_ref().borrow().value())
+        }
+        fn comparator(&self) -> &C {
+            &self.compare
+        }
+        fn before_compare(&self) {
+            self.counters.bump(|s| s.compare += 1);
+        }
+    }
+    impl<K> RedBlackTree<K> {
+        pub fn new() -> Self {
+            Self::with_comparator(Natural)
+        }
+    }
+    impl<K, C> RedBlackTree<K, C> {
+        pub fn with_comparator(compare:C) -> Self {
+            Self {
+                root: None,
+                len: 0,
+                id: next_tree_id(),
+                compare,
+                counters: Counters::default(),
+                tracer: None,
+                hooks: None
             }
         }
-        fn right_rotate(&mut self, x:&Rc<RefCell<Node>>) {
-            if let Some(p) = self.get_parent(x) {
-                let x_node = x.as_ref().borrow().clone();
-                let p_node = p.as_ref().borrow().clone();
-                if p_node.left == Some(x.clone()) {
-                    if let Some(g) = self.get_parent(&p) {
-                        let mut g_node = g.as_ref().borrow_mut();
-                        if g_node.left == Some(p.clone()) {
-                            g_node.left = Some(x.clone());
-                        } else {
-                            g_node.right = Some(x.clone());
-                        }
-                    }
-                    {
-                        let mut x_write = x.as_ref().borrow_mut();
+        // 从根往下走到叶子，摘下叶子再回到父节点。被释放的节点已经没有子节点，drop 不会递归，
+        // 每条边只走两次，O(n)
+        pub fn clear(&mut self) {
+            let mut x = self.root.clone();
+            while let Some(n) = x {
+                let child = {
+                    let n_node = n.as_ref().borrow();
+                    n_node.left.clone().or_else(|| n_node.right.clone())
+                };
+                if child.is_some() {
+                    x = child;
+                    continue;
+                }
+                self.notify_remove(&n);
+                let p = n.as_ref().borrow().parent_node();
+                {
+                    let mut n_write = n.as_ref().borrow_mut();
+                    n_write.parent = None;
+                    n_write.owner = 0;
+                }
+                match &p {
+                    Some(p) => {
                         let mut p_write = p.as_ref().borrow_mut();
-                        if let Some(x_right) = x_node.right.clone() {
-                            let mut x_right_write = x_right.as_ref().borrow_mut();
-                            x_right_write.parent = Some(Rc::downgrade(&p));
+                        if is_same(&p_write.left, &n) {
+                            p_write.left = None;
+                        } else {
+                            p_write.right = None;
                         }
-                        p_write.left = x_node.right.clone();
-                        p_write.parent = Some(Rc::downgrade(x));
-                        x_write.right = Some(p.clone());
-                        x_write.parent = p_node.parent.clone();
-                    }
-                    if self.root == Some(p) {
-                        self.root = Some(x.clone());
-                    }
+                    },
+                    None => self.root = None
                 }
+                x = p;
             }
+            self.len = 0;
+        }
+    }
+    impl<K, C: Compare<K>> RedBlackTree<K, C> {
+        #[cfg(feature = "instrument")]
+        pub fn counters(&self) -> CounterSnapshot {
+            self.counters.snapshot()
+        }
+        #[cfg(feature = "instrument")]
+        pub fn reset_counters(&self) {
+            self.counters.reset();
         }
         pub fn len(&self) -> usize {
             self.len
         }
-        pub fn get(&self, value:i32) -> Option<Rc<RefCell<Node>>> {
-            self.insert_search(value, true)
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
         }
-        pub fn add(&mut self, value:i32) {
-            match self.insert_search(value, false) {
-                None => {
-                    self.root = Some(Rc::new(RefCell::new(Node::new(value, Color::Black, None))));
-                    self.len = 1;
-                },
-                Some(pv) => {
-                    let pv_node = pv.as_ref().borrow().clone();
-                    let x = Rc::new(RefCell::new(Node::new(value, Color::Red, Some(Rc::downgrade(&pv)))));
-                    {
-                        let mut pv_write = pv.as_ref().borrow_mut();
-                        if value < pv_node.value  {
-                            pv_write.left = Some(x.clone());
-                            self.len += 1;
-                        } else if value > pv_node.value {
-                            pv_write.right = Some(x.clone());
-                            self.len += 1;
-                        } else if value == pv_node.value {
-                            pv_write.value = value;
-                        }
-                    }
-                    self.insert_fix(x);
-                }
-            }
+        pub fn get<Q: ?Sized>(&self, value:&Q) -> Option<NodeRef<K>>
+        where K: Borrow<Q>, C: Compare<Q> {
+            balance::find(self, value).ok().map(|x| x.0)
         }
-        fn insert_fix(&mut self, x:Rc<RefCell<Node>>) {
-            let mut x = x;
-            loop {
-                let x_node = x.as_ref().borrow().clone();
-                if x_node.color == Color::Red {
-                    if let Some(p) = self.get_parent(&x) {
-                        let p_node = p.as_ref().borrow().clone();
-                        if p_node.color == Color::Red {
-                            if let Some(g) = self.get_parent(&p) {
-                                let g_node = g.clone().as_ref().borrow().clone();
-                                if g_node.color == Color::Black {
-                                    let next;
-                                    {
-                                        let mut x = x;
-                                        let mut p = p;
-                                        let u = self.get_brother(&p);
-                                        // 不能判断U，U可能不存在，用P判断
-                                        if self.is_right_node(&p) {
-                                            if self.is_left_node(&x) {
-                                                self.right_rotate(&x);
-                                            } else {
-                                                (x, p) = (p, x);
-                                            }
-                                            self.left_rotate(&x);
-                                        } else {
-                                            if self.is_right_node(&x) {
-                                                self.left_rotate(&x);
-                                            } else {
-                                                (x, p) = (p, x);
-                                            }
-                                            self.right_rotate(&x);
-                                        }
-                                        if let Some(u) = u {
-                                            let u_node = u.as_ref().borrow().clone();
-                                            if u_node.color == Color::Red {
-                                                let mut p_write = p.as_ref().borrow_mut();
-                                                p_write.color = Color::Black;
-                                            } else /*if u_node.color == Color::Black*/ {
-                                                let mut x_write = x.as_ref().borrow_mut();
-                                                x_write.color = Color::Black;
-                                                let mut g_write = g.as_ref().borrow_mut();
-                                                g_write.color = Color::Red;
-                                            }
-                                        } else /* u.is_none() */ {
-                                            let mut p_write = p.as_ref().borrow_mut();
-                                            p_write.color = Color::Black;
-                                        }
-                                        next = x;
-                                    }
-                                    x = next;
-                                    continue;
-                                }
-                            }
-                        }
-                    }
-                }
-                break;
-            }
-            self.root_fix();
-        }
-        fn delete_fix(&mut self, x:Rc<RefCell<Node>>) {
-            let mut x = x;
-            loop {
-                let x_node = x.as_ref().borrow().clone();
-                if x_node.color == Color::Black {
-                    if let (Some(p), Some(b)) = (self.get_parent(&x), self.get_brother(&x)) {
-                        let p_node = p.as_ref().borrow().clone();
-                        let b_node = b.as_ref().borrow().clone();
-                        match p_node.color {
-                            Color::Red => {
-                                let cl;
-                                let cr;
-                                let cl_node;
-                                let cr_node;
-                                if self.is_right_node(&x) {
-                                    cl  = b_node.left.clone().unwrap();
-                                    cr = b_node.right.clone().unwrap();
-                                } else {
-                                    cl  = b_node.right.clone().unwrap();
-                                    cr = b_node.left.clone().unwrap();
-                                }
-                                cl_node = cl.as_ref().borrow().clone();
-                                cr_node = cr.as_ref().borrow().clone();
-                                match (cl_node.color, cr_node.color) {
-                                    (Color::Black, Color::Black) => {
-                                        //规则1 P红 B黑 双C黑
-                                        self.set_color(&p, Color::Black);
-                                        self.set_color(&b, Color::Red);
-                                    },(Color::Red, Color::Red) => {
-                                        //规则2 P红 B黑 双C红
-                                        if self.is_right_node(&x) {
-                                            self.right_rotate(&b);
-                                        } else {
-                                            self.left_rotate(&b);
-                                        }
-                                        self.set_color(&cl, Color::Black);
-                                        self.set_color(&p, Color::Black);
-                                        self.set_color(&b, Color::Red);
-                                    },(Color::Red, Color::Black) => {
-                                        //规则3 P红 B黑 CL红 CR黑
-                                        if self.is_right_node(&x) {
-                                            self.right_rotate(&b);
-                                        } else {
-                                            self.left_rotate(&b);
-                                        }
-                                    },(Color::Black, Color::Red) => {
-                                        //规则4 P红 B黑 CL黑 CR红
-                                        if self.is_right_node(&x) {
-                                            self.left_rotate(&cr);
-                                            self.right_rotate(&cr);
-                                            self.set_color(&b, Color::Red);
-                                            self.set_color(&cr, Color::Black);
-                                        } else {
-                                            self.right_rotate(&cr);
-                                            self.left_rotate(&cr);
-                                            self.set_color(&b, Color::Red);
-                                            self.set_color(&cr, Color::Black);
-                                        }
-                                    }
-                                }
-                            },
-                            Color::Black => {
-                                match b_node.color {
-                                    Color::Red => {
-                                        let c;
-                                        let c_node;
-                                        let l;
-                                        let r;
-                                        let l_node;
-                                        let r_node;
-                                        if self.is_right_node(&x) {
-                                            c = b_node.right.clone().unwrap();
-                                            c_node = c.as_ref().borrow().clone();
-                                            l = c_node.left.clone().unwrap();
-                                            r = c_node.right.clone().unwrap();
-                                        } else {
-                                            c = b_node.left.clone().unwrap();
-                                            c_node = c.as_ref().borrow().clone();
-                                            l = c_node.right.clone().unwrap();
-                                            r = c_node.left.clone().unwrap();
-                                        }
-                                        l_node = l.as_ref().borrow().clone();
-                                        r_node = r.as_ref().borrow().clone();    
-                                        match (l_node.color, r_node.color) {
-                                            (Color::Black, Color::Black) => {
-                                                //规则5 P黑 B红 C双子(双黑)
-                                                if self.is_right_node(&x) {
-                                                    self.right_rotate(&b);
-                                                } else {
-                                                    self.left_rotate(&b);
-                                                }
-                                                self.set_color(&b, Color::Black);
-                                                self.set_color(&c, Color::Red);
-                                            },
-                                            (Color::Red, Color::Red) => {
-                                                //规则6 P黑 B红 C双子(双红)
-                                                if self.is_right_node(&x) {
-                                                    self.right_rotate(&b);
-                                                    self.right_rotate(&c);
-                                                    self.left_rotate(&c);
-                                                } else {
-                                                    self.left_rotate(&b);
-                                                    self.left_rotate(&c);
-                                                    self.right_rotate(&c);
-                                                }
-                                                self.set_color(&l, Color::Black);
-                                            },
-                                            (Color::Red, Color::Black) => {
-                                                //规则7 P黑 B红 C双子(左红右黑)
-                                                if self.is_right_node(&x) {
-                                                    self.right_rotate(&b);
-                                                    self.right_rotate(&c);
-                                                    self.left_rotate(&c);
-                                                } else {
-                                                    self.left_rotate(&b);
-                                                    self.left_rotate(&c);
-                                                    self.right_rotate(&c);
-                                                }
-                                                self.set_color(&l, Color::Black);
-                                            },
-                                            (Color::Black, Color::Red) => {
-                                                //规则8 P黑 B红 C双子(左黑右红)
-                                                if self.is_right_node(&x) {
-                                                    self.right_rotate(&b);
-                                                    self.left_rotate(&r);
-                                                    self.right_rotate(&r);
-                                                } else {
-                                                    self.left_rotate(&b);
-                                                    self.right_rotate(&r);
-                                                    self.left_rotate(&r);
-                                                }
-                                                self.set_color(&b, Color::Black);
-                                            }
-                                        }
-                                    },
-                                    Color::Black => {
-                                        let cl;
-                                        let cr;
-                                        let cl_node;
-                                        let cr_node;
-                                        if self.is_right_node(&x) {
-                                            cl  = b_node.left.clone().unwrap();
-                                            cr = b_node.right.clone().unwrap();
-                                        } else {
-                                            cl  = b_node.right.clone().unwrap();
-                                            cr = b_node.left.clone().unwrap();
-                                        }
-                                        cl_node = cl.as_ref().borrow().clone();
-                                        cr_node = cr.as_ref().borrow().clone();
-                                        match (cl_node.color, cr_node.color) {
-                                            (Color::Black, Color::Black) => {
-                                                //规则9 P黑 B黑 双C黑(递归P)
-                                                self.set_color(&b, Color::Red);
-                                                x = p;
-                                                continue;
-                                            },(Color::Red, Color::Red) => {
-                                                //规则10 P黑 B黑 双C红
-                                                if self.is_right_node(&x) {
-                                                    self.right_rotate(&b);
-                                                } else {
-                                                    self.left_rotate(&b);
-                                                }
-                                                self.set_color(&cl, Color::Black);
-                                            },(Color::Black, Color::Red) => {
-                                                //规则11 P黑 B黑 CL黑 CR红
-                                                if self.is_right_node(&x) {
-                                                    self.left_rotate(&cr);
-                                                    self.right_rotate(&cr);
-                                                } else {
-                                                    self.right_rotate(&cr);
-                                                    self.left_rotate(&cr);
-                                                }
-                                                self.set_color(&cr, Color::Black);
-                                            },(Color::Red, Color::Black) => {
-                                                //规则12 P黑 B黑 CL红 CR黑
-                                                if self.is_right_node(&x) {
-                                                    self.right_rotate(&b);
-                                                } else {
-                                                    self.left_rotate(&b);
-                                                }
-                                                self.set_color(&cl, Color::Black);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                break;
-            }
+        pub fn contains<Q: ?Sized>(&self, value:&Q) -> bool
+        where K: Borrow<Q>, C: Compare<Q> {
+            self.get(value).is_some()
+        }
+        // 小于等于 value 的最大节点
+        pub fn floor<Q: ?Sized>(&self, value:&Q) -> Option<NodeRef<K>>
+        where K: Borrow<Q>, C: Compare<Q> {
+            balance::floor(self, value).map(|x| x.0)
+        }
+        // 大于等于 value 的最小节点
+        pub fn ceiling<Q: ?Sized>(&self, value:&Q) -> Option<NodeRef<K>>
+        where K: Borrow<Q>, C: Compare<Q> {
+            balance::ceiling(self, value).map(|x| x.0)
+        }
+        pub fn iter(&self) -> Iter<'_, K> {
+            Iter::new(balance::first(self).map(|x| x.0), None)
+        }
+        pub fn range<Q: ?Sized, R: RangeBounds<Q>>(&self, range:R) -> Iter<'_, K>
+        where K: Borrow<Q>, C: Compare<Q> {
+            let (start, end) = balance::range(self, range).into_bounds();
+            Iter::new(start.map(|x| x.0), end.map(|x| x.0))
         }
-        pub fn del(&mut self, value:i32) {
+        pub fn add(&mut self, value:K) {
+            self.insert_node(value);
+        }
+        // 返回存放 value 的节点
+        fn insert_node(&mut self, value:K) -> NodeRef<K> {
+            self.trace_begin("add", &value);
+            let slot = match balance::find(self, &value) {
+                Ok(pv) => {
+                    // 比较器认为相等的键直接替换
+                    pv.0.as_ref().borrow_mut().value = Some(value);
+                    self.trace_end();
+                    self.notify_insert(&pv.0);
+                    return pv.0;
+                },
+                Err(slot) => slot
+            };
+            let mut node = Node::new(value, Color::Red, None);
+            node.owner = self.id;
+            let x = Rc::new(RefCell::new(node));
+            balance::insert_at(self, NodePtr(x.clone()), slot);
+            self.len += 1;
+            self.trace_end();
+            self.notify_insert(&x);
+            x
+        }
+        pub fn remove<Q: ?Sized>(&mut self, value:&Q) -> bool
+        where K: Borrow<Q>, C: Compare<Q> {
             match self.get(value) {
-                None => return,
+                None => false,
                 Some(x) => {
-                    let mut x = x.clone();
-                    if let Some(pv) = self.delete_search(&x) {
-                        self.swap_value(&x, &pv);
-                        x = pv.clone();
-                    }
-                    loop {
-                        let x_node = x.as_ref().borrow().clone();
-                        match x_node.color {
-                            // 规则1 X红
-                            Color::Red => {
-                                self.drop_node(x);
-                            },
-                            Color::Black => {
-                                // 规则2 X黑 无父无子
-                                if x_node.parent.is_none() && x_node.left.is_none() && x_node.right.is_none() {
-                                    self.drop_node(x);
-                                } else {
-                                    // 规则3 X黑有一个子节点
-                                    if let Some(c) = x_node.left.clone() {
-                                        self.swap_value(&x, &c);
-                                        x = c;
-                                        continue;
-                                    } else if let Some(c) = x_node.right.clone() {
-                                        self.swap_value(&x, &c);
-                                        x = c;
-                                        continue;
-                                    } else {
-                                        if let (Some(p), Some(mut b)) = (self.get_parent(&x), self.get_brother(&x)) {
-                                            let p_node = p.as_ref().borrow().clone();
-                                            let b_node = b.as_ref().borrow().clone();
-                                            match p_node.color {
-                                                Color::Red => {
-                                                    if b_node.color != Color::Black {
-                                                        panic!("节点不平衡");
-                                                    }
-                                                    //规则4 X黑 P红 B黑
-                                                    let (c_a, c_b);
-                                                    if self.is_left_node(&x) {
-                                                        c_a = b_node.left.clone();
-                                                        c_b = b_node.right.clone();
-                                                    } else {
-                                                        c_a = b_node.right.clone();
-                                                        c_b = b_node.left.clone();
-                                                    }
-                                                    if let Some(c) = c_a {
-                                                        let c_node = c.as_ref().borrow().clone();
-                                                        if c_node.color != Color::Red {
-                                                            panic!("节点不平衡");
-                                                        }
-                                                        self.swap_value(&x, &p);
-                                                        self.swap_value(&p, &c);
-                                                        x = c;
-                                                        continue;
-                                                    } if let Some(c) = c_b {
-                                                        let c_node = c.as_ref().borrow().clone();
-                                                        if c_node.color != Color::Red {
-                                                            panic!("节点不平衡");
-                                                        }
-                                                        self.swap_value(&x, &p);
-                                                        self.swap_value(&p, &b);
-                                                        self.swap_value(&b, &c);
-                                                        x = c;
-                                                        continue;
-                                                    } else {
-                                                        self.set_color(&p, Color::Black);
-                                                        self.set_color(&b, Color::Red);
-                                                        self.drop_node(x);
-                                                    }
-                                                }, Color::Black => {
-                                                    match b_node.color {
-                                                        Color::Red => {
-                                                            //规则5 X黑 P黑 B红
-                                                            let c;
-                                                            if self.is_left_node(&x) {
-                                                                c = b_node.left.clone();
-                                                            } else {
-                                                                c = b_node.right.clone();
-                                                            }
-                                                            if let Some(c) = c {
-                                                                let c_node = c.as_ref().borrow().clone();
-                                                                if c_node.color != Color::Black {
-                                                                    panic!("节点不平衡");
-                                                                }
-                                                                // C可能有红子节点
-                                                                let c_node = c.as_ref().borrow().clone();
-                                                                let (c_a, c_b);
-                                                                if self.is_left_node(&x) {
-                                                                    c_a = c_node.left.clone();
-                                                                    c_b = c_node.right.clone();
-                                                                } else {
-                                                                    c_a = c_node.right.clone();
-                                                                    c_b = c_node.left.clone();
-                                                                }
-                                                                if let Some(c_a) = c_a {
-                                                                    self.swap_value(&x, &p);
-                                                                    self.swap_value(&p, &c_a);
-                                                                    x = c_a;
-                                                                    // 转到规则1
-                                                                    continue;
-                                                                } else if let Some(c_b) = c_b {
-                                                                    self.swap_value(&x, &p);
-                                                                    self.swap_value(&p, &c);
-                                                                    self.swap_value(&c, &c_b);
-                                                                    x = c_b;
-                                                                    // 转到规则1
-                                                                    continue;
-                                                                } else {
-                                                                    self.swap_value(&x, &p);
-                                                                    self.swap_value(&p, &c);
-                                                                    x = c;
-                                                                    // 转到规则4
-                                                                    continue;
-                                                                }
-                                                            } else {
-                                                                panic!("节点不平衡");
-                                                            }
-                                                        },
-                                                        Color::Black => {
-                                                            //规则6 X黑 P黑 B黑
-                                                            let (c_a, c_b);
-                                                            if self.is_left_node(&x) {
-                                                                c_a = b_node.left.clone();
-                                                                c_b = b_node.right.clone();
-                                                            } else {
-                                                                c_a = b_node.right.clone();
-                                                                c_b = b_node.left.clone();
-                                                            }
-                                                            if let Some(c) = c_a {
-                                                                let c_node = c.as_ref().borrow().clone();
-                                                                if c_node.color != Color::Red {
-                                                                    panic!("节点不平衡");
-                                                                }
-                                                                self.swap_value(&x, &p);
-                                                                self.swap_value(&p, &c);
-                                                                x = c;
-                                                                continue;
-                                                            } else if let Some(c) = c_b {
-                                                                let c_node = c.as_ref().borrow().clone();
-                                                                if c_node.color != Color::Red {
-                                                                    panic!("节点不平衡");
-                                                                }
-                                                                self.swap_value(&x, &p);
-                                                                self.swap_value(&p, &b);
-                                                                self.swap_value(&b, &c);
-                                                                x = c;
-                                                                continue;
-                                                            } else {
-                                                                /* 无子节点 */
-                                                                self.set_color(&p, Color::Black);
-                                                                self.set_color(&b, Color::Red);
-                                                                self.set_color(&x, Color::Red);
-                                                                // P有父
-                                                                if p_node.parent.is_some() {
-                                                                    if self.is_left_node(&x) {
-                                                                        if self.is_right_node(&p) {
-                                                                            self.swap_value(&x, &b);
-                                                                            (x, b) = (b, x);
-                                                                            self.swap_value(&b, &p);
-                                                                        }
-                                                                    } else {
-                                                                        if self.is_left_node(&p) {
-                                                                            self.swap_value(&x, &b);
-                                                                            (x, b) = (b, x);
-                                                                            self.swap_value(&b, &p);
-                                                                        }
-                                                                    }
-                                                                    self.drop_node(x);
-                                                                    self.delete_fix(p);
-                                                                }
-                                                                // P无父
-                                                                else {
-                                                                    self.drop_node(x);
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        } else {
-                                            panic!("节点不平衡");
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        break;
-                    }
-                    self.root_fix();
+                    self.delete_node(x);
+                    true
                 }
             }
         }
-        pub fn clear(&mut self) {
-            while let Some(x) = self.root.clone() {
-                let v = x.as_ref().borrow().clone().value;
-                self.del(v);
-            }
+        pub fn del<Q: ?Sized>(&mut self, value:&Q)
+        where K: Borrow<Q>, C: Compare<Q> {
+            self.remove(value);
         }
-        fn check_tree(&self) -> bool {
-            let mut hight = None;
-            let mut result = true;
-            self._check_rb_hight(self.root.clone(), 0, &mut |x| {
-                if hight.is_none() {
-                    hight = Some(x);
-                }
-                if result {
-                    result = hight == Some(x);
-                }
-            });
-            result
-        }
-        fn _check_rb_hight(&self, x:Option<Rc<RefCell<Node>>>, i:i32, callback: &mut dyn FnMut(i32)) {
-            let mut i = i;
-            if x.is_some() {
-                let temp = x.clone().unwrap();
-                let x_node = temp.as_ref().borrow().clone();
-                if x_node.color == Color::Black {
-                    i+=1;
-                }
-                self._check_rb_hight(x_node.left.clone(), i, callback);
-                self._check_rb_hight(x_node.right.clone(), i, callback);
-        
-            } else {
-                callback(i);
-            }
+        fn delete_node(&mut self, x:NodeRef<K>) {
+            self.trace_begin("del", x.as_ref().borrow().value());
+            balance::delete(self, NodePtr(x.clone()));
+            x.as_ref().borrow_mut().owner = 0;
+            self.len -= 1;
+            self.trace_end();
+        }
+        pub fn check_tree(&self) -> bool {
+            self.validate().is_ok()
         }
     }
-}
\ No newline at end of file
+}
@@ -1,7 +1,202 @@
+use std::env;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::process;
+
+use rb_tree::oplog::{replay, ReplayError};
 use rb_tree::rbtree_mod::*;
 
-fn main() {
-    let mut t = RedBlackTree::new();
-    t.add(1);
-    println!("{:#?}", t);
+const HELP: &str = "\
+commands:
+  add <k>...        插入
+  del <k>...        删除
+  get <k>           查找
+  range <a> <b>     列出 [a, b] 内的键
+  print             打印树
+  dot               输出 DOT
+  validate          检查红黑树性质
+  stats             统计信息
+  memory            内存占用
+  undo              撤销上一次修改
+  clear             清空
+  show on|off       每次修改后是否打印树
+  help              帮助
+  quit              退出";
+
+struct Repl {
+    tree: RedBlackTree<i32>,
+    history: Vec<RedBlackTree<i32>>,
+    show: bool
+}
+
+fn parse_keys(args:&[&str]) -> Result<Vec<i32>, String> {
+    if args.is_empty() {
+        return Err("missing key".to_string());
+    }
+    args.iter()
+        .map(|a| a.parse::<i32>().map_err(|e| format!("bad key {:?}: {}", a, e)))
+        .collect()
+}
+
+impl Repl {
+    fn new() -> Self {
+        Self {
+            tree: RedBlackTree::new(),
+            history: vec![],
+            show: false
+        }
+    }
+    fn save(&mut self) {
+        self.history.push(self.tree.clone());
+    }
+    // 返回 false 表示退出
+    fn run(&mut self, line:&str, out:&mut impl Write) -> io::Result<bool> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&cmd, args)) = words.split_first() else {
+            return Ok(true);
+        };
+        let mut changed = false;
+        match (cmd, args) {
+            ("add", _) | ("del", _) => match parse_keys(args) {
+                Ok(keys) => {
+                    // 第一次真正修改前才复制，没有改动的命令不进入 undo 历史
+                    let mut before = None;
+                    let add = cmd == "add";
+                    for k in keys {
+                        if self.tree.contains(&k) == add {
+                            if !add {
+                                writeln!(out, "{} not found", k)?;
+                            }
+                            continue;
+                        }
+                        before.get_or_insert_with(|| self.tree.clone());
+                        if add {
+                            self.tree.add(k);
+                        } else {
+                            self.tree.remove(&k);
+                        }
+                    }
+                    changed = before.is_some();
+                    self.history.extend(before);
+                },
+                Err(e) => writeln!(out, "error: {}", e)?
+            },
+            ("get", [k]) => match k.parse::<i32>() {
+                Ok(k) => match self.tree.get(&k) {
+                    Some(x) => writeln!(out, "{} {:?}", x.borrow().value(), x.borrow().color())?,
+                    None => writeln!(out, "{} not found", k)?
+                },
+                Err(e) => writeln!(out, "error: {}", e)?
+            },
+            ("range", [a, b]) => match (a.parse::<i32>(), b.parse::<i32>()) {
+                (Ok(a), Ok(b)) => {
+                    let keys: Vec<String> = self.tree.range(a..=b).map(|x| x.borrow().value().to_string()).collect();
+                    writeln!(out, "[{}]", keys.join(", "))?;
+                },
+                _ => writeln!(out, "error: range <a> <b>")?
+            },
+            ("print", []) => write!(out, "{}", self.tree.frame().to_ascii())?,
+            ("dot", []) => write!(out, "{}", self.tree.frame().to_dot())?,
+            ("validate", []) => writeln!(out, "{}", if self.tree.check_tree() { "ok" } else { "INVALID" })?,
+            ("stats", []) => writeln!(out, "{}", self.tree.stats())?,
+            ("memory", []) => writeln!(out, "{}", self.tree.memory_usage())?,
+            ("undo", []) => match self.history.pop() {
+                Some(t) => {
+                    self.tree = t;
+                    changed = true;
+                },
+                None => writeln!(out, "nothing to undo")?
+            },
+            ("clear", []) => if !self.tree.is_empty() {
+                self.save();
+                self.tree.clear();
+                changed = true;
+            },
+            ("show", ["on"]) => self.show = true,
+            ("show", ["off"]) => self.show = false,
+            ("help", []) => writeln!(out, "{}", HELP)?,
+            ("quit", []) | ("exit", []) => return Ok(false),
+            _ => writeln!(out, "unknown command: {} (try help)", line.trim())?
+        }
+        if changed && self.show {
+            write!(out, "{}", self.tree.frame().to_ascii())?;
+        }
+        Ok(true)
+    }
+}
+
+const USAGE: &str = "usage: rb_tree                  交互模式，从 stdin 读取命令
+       rb_tree replay <log> [--check N] [--str]
+                                 回放操作日志，每 N 个操作检查一次，--str 表示键为字符串";
+
+fn run_replay(args:&[String]) -> Result<(), String> {
+    let mut path = None;
+    let mut every = 0;
+    let mut strings = false;
+    let mut args = args.iter();
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--check" => {
+                let n = args.next().ok_or("--check needs a value")?;
+                every = n.parse().map_err(|e| format!("bad --check {:?}: {}", n, e))?;
+            },
+            "--str" => strings = true,
+            _ if path.is_none() => path = Some(a),
+            _ => return Err(format!("unexpected argument {:?}", a))
+        }
+    }
+    let path = path.ok_or("missing log file")?;
+    let input = BufReader::new(File::open(path).map_err(|e| format!("{}: {}", path, e))?);
+    fn show<K>(result:Result<(RedBlackTree<K>, impl Display), ReplayError>) -> Result<(), String> {
+        let (_, report) = result.map_err(|e| e.to_string())?;
+        println!("{}", report);
+        Ok(())
+    }
+    if strings {
+        show(replay::<String>(input, every))
+    } else {
+        show(replay::<i32>(input, every))
+    }
+}
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => {},
+        Some("replay") => {
+            if let Err(e) = run_replay(&args[1..]) {
+                eprintln!("error: {}\n{}", e, USAGE);
+                process::exit(1);
+            }
+            return Ok(());
+        },
+        Some(_) => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    }
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    let mut out = io::stdout();
+    let mut repl = Repl::new();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if interactive {
+            write!(out, "> ")?;
+            out.flush()?;
+        }
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let line = line?;
+        // # 开头为注释，方便写脚本
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        if !repl.run(&line, &mut out)? {
+            break;
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,292 @@
+use std::cell::RefCell;
+use std::fmt::{self, Display};
+use std::io::{self, BufRead, Write};
+use std::ops::{Bound, RangeBounds};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::rbtree_mod::{Compare, InvariantError, Iter, Natural, Node, RedBlackTree};
+
+// v2 起键经过转义，不含反斜杠的 v1 日志可以照常回放
+pub const HEADER: &str = "# rb_tree oplog v2";
+
+/// 键中的反斜杠和空白字符转义成 \\ \s \t \n \r，空键写成 \e，一个键总是一个不含空白的词
+pub fn escape_key(key:&str) -> String {
+    if key.is_empty() {
+        return "\\e".to_string();
+    }
+    let mut out = String::with_capacity(key.len());
+    for c in key.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ' ' => out.push_str("\\s"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(c)
+        }
+    }
+    out
+}
+
+/// escape_key 的逆操作，遇到未知的转义时返回 None
+pub fn unescape_key(text:&str) -> Option<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        out.push(match chars.next()? {
+            '\\' => '\\',
+            'e' => continue,
+            's' => ' ',
+            't' => '\t',
+            'n' => '\n',
+            'r' => '\r',
+            _ => return None
+        });
+    }
+    Some(out)
+}
+
+// 范围写成 "[a b)" 的形式，无界的一端写成 *
+fn lower_text<K: Display>(bound:Bound<&K>) -> String {
+    match bound {
+        Bound::Included(k) => format!("[{}", escape_key(&k.to_string())),
+        Bound::Excluded(k) => format!("({}", escape_key(&k.to_string())),
+        Bound::Unbounded => "*".to_string()
+    }
+}
+
+fn upper_text<K: Display>(bound:Bound<&K>) -> String {
+    match bound {
+        Bound::Included(k) => format!("{}]", escape_key(&k.to_string())),
+        Bound::Excluded(k) => format!("{})", escape_key(&k.to_string())),
+        Bound::Unbounded => "*".to_string()
+    }
+}
+
+/// 包装一棵树，把 add / del / get / contains / range / floor / ceiling 逐行写入日志
+pub struct Recorder<K, W: Write, C = Natural> {
+    tree: RedBlackTree<K, C>,
+    out: W,
+    // 写日志出错后不再写入，在 finish 时返回
+    error: Option<io::Error>
+}
+
+impl<K: Display, W: Write, C: Compare<K>> Recorder<K, W, C> {
+    pub fn new(tree:RedBlackTree<K, C>, out:W) -> Self {
+        let mut r = Self {
+            tree,
+            out,
+            error: None
+        };
+        r.write(format_args!("{}", HEADER));
+        r
+    }
+    fn write(&mut self, line:fmt::Arguments<'_>) {
+        if self.error.is_none() {
+            if let Err(e) = writeln!(self.out, "{}", line) {
+                self.error = Some(e);
+            }
+        }
+    }
+    pub fn tree(&self) -> &RedBlackTree<K, C> {
+        &self.tree
+    }
+    fn write_key(&mut self, op:&str, value:&K) {
+        let key = escape_key(&value.to_string());
+        self.write(format_args!("{} {}", op, key));
+    }
+    pub fn add(&mut self, value:K) {
+        self.write_key("add", &value);
+        self.tree.add(value);
+    }
+    pub fn del(&mut self, value:&K) {
+        self.write_key("del", value);
+        self.tree.del(value);
+    }
+    pub fn remove(&mut self, value:&K) -> bool {
+        self.write_key("del", value);
+        self.tree.remove(value)
+    }
+    pub fn contains(&mut self, value:&K) -> bool {
+        self.write_key("get", value);
+        self.tree.contains(value)
+    }
+    pub fn get(&mut self, value:&K) -> Option<Rc<RefCell<Node<K>>>> {
+        self.write_key("get", value);
+        self.tree.get(value)
+    }
+    pub fn floor(&mut self, value:&K) -> Option<Rc<RefCell<Node<K>>>> {
+        self.write_key("floor", value);
+        self.tree.floor(value)
+    }
+    pub fn ceiling(&mut self, value:&K) -> Option<Rc<RefCell<Node<K>>>> {
+        self.write_key("ceiling", value);
+        self.tree.ceiling(value)
+    }
+    pub fn range<R: RangeBounds<K>>(&mut self, range:R) -> Iter<'_, K> {
+        let (lower, upper) = (lower_text(range.start_bound()), upper_text(range.end_bound()));
+        self.write(format_args!("range {} {}", lower, upper));
+        self.tree.range(range)
+    }
+    // 写入一个检查点，回放时在这里检查红黑树性质
+    pub fn checkpoint(&mut self) {
+        self.write(format_args!("check"));
+    }
+    pub fn finish(mut self) -> io::Result<(RedBlackTree<K, C>, W)> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        self.out.flush()?;
+        Ok((self.tree, self.out))
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Parse { line: usize, text: String },
+    Invalid { line: usize, error: InvariantError }
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "{}", e),
+            ReplayError::Parse { line, text } => write!(f, "line {}: cannot parse {:?}", line, text),
+            ReplayError::Invalid { line, error } => write!(f, "line {}: {}", line, error)
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(e:io::Error) -> Self {
+        ReplayError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpStats {
+    pub count: u64,
+    pub time: Duration
+}
+
+impl OpStats {
+    fn record(&mut self, t:Duration) {
+        self.count += 1;
+        self.time += t;
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    pub add: OpStats,
+    pub del: OpStats,
+    pub get: OpStats,
+    pub range: OpStats,
+    pub floor: OpStats,
+    pub ceiling: OpStats,
+    pub checkpoints: u64,
+    pub len: usize
+}
+
+impl Display for ReplayReport {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "op         count        total      per op")?;
+        let ops = [
+            ("add", self.add),
+            ("del", self.del),
+            ("get", self.get),
+            ("range", self.range),
+            ("floor", self.floor),
+            ("ceiling", self.ceiling)
+        ];
+        for (name, s) in ops {
+            let per = if s.count > 0 { s.time / s.count as u32 } else { Duration::ZERO };
+            writeln!(f, "{:<7} {:>8} {:>12.3?} {:>11.1?}", name, s.count, s.time, per)?;
+        }
+        write!(f, "checkpoints: {}, final len: {}", self.checkpoints, self.len)
+    }
+}
+
+/// 重新执行日志。除了日志里的 check 行，每 every 个操作也检查一次（0 表示不额外检查），结束时总会检查
+pub fn replay<K: FromStr + Ord>(input:impl BufRead, every:usize) -> Result<(RedBlackTree<K>, ReplayReport), ReplayError> {
+    let mut tree = RedBlackTree::new();
+    let mut report = ReplayReport::default();
+    let mut ops = 0;
+    let mut last = 0;
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        let text = line.trim();
+        last = i + 1;
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let parse_error = || ReplayError::Parse { line: i + 1, text: text.to_string() };
+        let check = |tree:&RedBlackTree<K>| tree.validate().map_err(|error| ReplayError::Invalid { line: i + 1, error });
+        if text == "check" {
+            check(&tree)?;
+            report.checkpoints += 1;
+            continue;
+        }
+        let (op, arg) = text.split_once(' ').ok_or_else(parse_error)?;
+        let key = |text:&str| unescape_key(text).and_then(|k| k.parse::<K>().ok()).ok_or_else(parse_error);
+        let t = Instant::now();
+        match op {
+            "add" => {
+                tree.add(key(arg)?);
+                report.add.record(t.elapsed());
+            },
+            "del" => {
+                tree.del(&key(arg)?);
+                report.del.record(t.elapsed());
+            },
+            "get" => {
+                tree.contains(&key(arg)?);
+                report.get.record(t.elapsed());
+            },
+            "floor" => {
+                tree.floor(&key(arg)?);
+                report.floor.record(t.elapsed());
+            },
+            "ceiling" => {
+                tree.ceiling(&key(arg)?);
+                report.ceiling.record(t.elapsed());
+            },
+            "range" => {
+                let (lower, upper) = arg.split_once(' ').ok_or_else(parse_error)?;
+                let lower = match lower {
+                    "*" => Bound::Unbounded,
+                    _ if lower.starts_with('[') => Bound::Included(key(&lower[1..])?),
+                    _ if lower.starts_with('(') => Bound::Excluded(key(&lower[1..])?),
+                    _ => return Err(parse_error())
+                };
+                let upper = match upper {
+                    "*" => Bound::Unbounded,
+                    _ if upper.ends_with(']') => Bound::Included(key(&upper[..upper.len() - 1])?),
+                    _ if upper.ends_with(')') => Bound::Excluded(key(&upper[..upper.len() - 1])?),
+                    _ => return Err(parse_error())
+                };
+                tree.range((lower, upper)).count();
+                report.range.record(t.elapsed());
+            },
+            _ => return Err(parse_error())
+        }
+        ops += 1;
+        if every > 0 && ops % every == 0 {
+            check(&tree)?;
+            report.checkpoints += 1;
+        }
+    }
+    tree.validate().map_err(|error| ReplayError::Invalid { line: last, error })?;
+    report.checkpoints += 1;
+    report.len = tree.len();
+    Ok((tree, report))
+}
@@ -0,0 +1,892 @@
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::mem;
+use core::ops::{Bound, RangeBounds};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+
+use super::{Color, Compare, InvariantError, Rotation};
+
+/// 平衡算法看到的树：节点用 Ptr 表示 (索引、指针或 Rc)，只通过这些方法读写链接和颜色。
+/// 所有的树都实现这个 trait，共用下面按 理论/ 中的规则写的插入和删除
+pub(super) trait Links {
+    type Ptr: Clone + Eq;
+    fn root(&self) -> Option<Self::Ptr>;
+    fn set_root(&mut self, x:Option<Self::Ptr>);
+    fn left(&self, x:&Self::Ptr) -> Option<Self::Ptr>;
+    fn right(&self, x:&Self::Ptr) -> Option<Self::Ptr>;
+    fn parent(&self, x:&Self::Ptr) -> Option<Self::Ptr>;
+    fn color(&self, x:&Self::Ptr) -> Color;
+    fn set_left(&mut self, x:&Self::Ptr, c:Option<Self::Ptr>);
+    fn set_right(&mut self, x:&Self::Ptr, c:Option<Self::Ptr>);
+    fn set_parent(&mut self, x:&Self::Ptr, p:Option<Self::Ptr>);
+    fn set_color(&mut self, x:&Self::Ptr, color:Color);
+    // 以下通知给 RedBlackTree 的计数器、trace 和回调用，默认什么都不做
+    fn case(&mut self, _case:Case) {}
+    fn before_rotate(&mut self, _kind:Rotation, _x:&Self::Ptr) {}
+    fn before_recolor(&mut self, _x:&Self::Ptr, _from:Color, _to:Color) {}
+    fn before_swap(&mut self, _a:&Self::Ptr, _b:&Self::Ptr) {}
+    fn before_unlink(&mut self, _x:&Self::Ptr) {}
+}
+
+/// 修复过程中触发的规则，编号和 理论/ 中的图一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Case {
+    // insert_fix 每轮循环一次
+    InsertFixLoop,
+    // uncle 为 None 表示U不存在
+    InsertFix { uncle: Option<Color>, p_right: bool, x_right: bool },
+    // 有两个子节点，先和后继交换
+    Successor,
+    // del 规则1..=6
+    Del(usize),
+    // delete_fix 规则1..=12
+    DeleteFix(usize)
+}
+
+const DEL_LABELS: [&str; 6] = [
+    "X红",
+    "X黑 无父无子",
+    "X黑有一个子节点",
+    "X黑 P红 B黑",
+    "X黑 P黑 B红",
+    "X黑 P黑 B黑"
+];
+
+const DELETE_FIX_LABELS: [&str; 12] = [
+    "P红 B黑 双C黑",
+    "P红 B黑 双C红",
+    "P红 B黑 CL红 CR黑",
+    "P红 B黑 CL黑 CR红",
+    "P黑 B红 C双子(双黑)",
+    "P黑 B红 C双子(双红)",
+    "P黑 B红 C双子(左红右黑)",
+    "P黑 B红 C双子(左黑右红)",
+    "P黑 B黑 双C黑(递归P)",
+    "P黑 B黑 双C红",
+    "P黑 B黑 CL黑 CR红",
+    "P黑 B黑 CL红 CR黑"
+];
+
+impl Case {
+    // trace 中的步骤名
+    pub(super) fn label(self) -> String {
+        let side = |right| if right { "R" } else { "L" };
+        match self {
+            Case::InsertFixLoop => String::from("insert_fix"),
+            Case::InsertFix { uncle, p_right, x_right } => {
+                let uncle = match uncle {
+                    Some(Color::Red) => "红",
+                    Some(Color::Black) => "黑",
+                    None => "无"
+                };
+                format!("insert_fix P红 G黑 U{} {}{}", uncle, side(p_right), side(x_right))
+            },
+            Case::Successor => String::from("del 与后继交换"),
+            Case::Del(n) => format!("del 规则{} {}", n, DEL_LABELS[n - 1]),
+            Case::DeleteFix(n) => format!("delete_fix 规则{} {}", n, DELETE_FIX_LABELS[n - 1])
+        }
+    }
+}
+
+fn child<L: Links>(t:&L, x:&L::Ptr, right:bool) -> Option<L::Ptr> {
+    if right { t.right(x) } else { t.left(x) }
+}
+
+fn set_child<L: Links>(t:&mut L, x:&L::Ptr, right:bool, c:Option<L::Ptr>) {
+    if right { t.set_right(x, c) } else { t.set_left(x, c) }
+}
+
+fn is_left<L: Links>(t:&L, x:&L::Ptr) -> bool {
+    t.parent(x).is_some_and(|p| t.left(&p).as_ref() == Some(x))
+}
+
+fn is_right<L: Links>(t:&L, x:&L::Ptr) -> bool {
+    t.parent(x).is_some_and(|p| t.right(&p).as_ref() == Some(x))
+}
+
+fn brother<L: Links>(t:&L, x:&L::Ptr) -> Option<L::Ptr> {
+    let p = t.parent(x)?;
+    child(t, &p, t.left(&p).as_ref() == Some(x))
+}
+
+// 把 p 下的 old 换成 new，p 为空时换根，不修改 new 的父指针
+fn replace_child<L: Links>(t:&mut L, p:Option<&L::Ptr>, old:&L::Ptr, new:Option<L::Ptr>) {
+    match p {
+        None => t.set_root(new),
+        Some(p) => {
+            let right = t.right(p).as_ref() == Some(old);
+            set_child(t, p, right, new);
+        }
+    }
+}
+
+fn recolor<L: Links>(t:&mut L, x:&L::Ptr, color:Color) {
+    let old = t.color(x);
+    if old != color {
+        t.before_recolor(x, old, color);
+    }
+    t.set_color(x, color);
+}
+
+// x 上升到父节点的位置：左旋时 x 是右子节点，右旋时 x 是左子节点，否则不动
+fn rotate<L: Links>(t:&mut L, kind:Rotation, x:&L::Ptr) {
+    let right = kind == Rotation::Left;
+    let Some(p) = t.parent(x) else {
+        return;
+    };
+    if child(t, &p, right).as_ref() != Some(x) {
+        return;
+    }
+    // 确实要旋转时才通知，不成立的调用不计入
+    t.before_rotate(kind, x);
+    let inner = child(t, x, !right);
+    let g = t.parent(&p);
+    replace_child(t, g.as_ref(), &p, Some(x.clone()));
+    if let Some(inner) = &inner {
+        t.set_parent(inner, Some(p.clone()));
+    }
+    set_child(t, &p, right, inner);
+    t.set_parent(&p, Some(x.clone()));
+    set_child(t, x, !right, Some(p));
+    t.set_parent(x, g);
+}
+
+fn left_rotate<L: Links>(t:&mut L, x:&L::Ptr) {
+    rotate(t, Rotation::Left, x);
+}
+
+fn right_rotate<L: Links>(t:&mut L, x:&L::Ptr) {
+    rotate(t, Rotation::Right, x);
+}
+
+// 交换两个节点在树中的位置 (链接和颜色)，值留在节点上，所以其他 Ptr 一直指向同一个键。
+// 交换后 a、b 也互换，仍然表示原来的两个位置，调用处的情况分析和交换值时一样
+fn swap_nodes<L: Links>(t:&mut L, a:&mut L::Ptr, b:&mut L::Ptr) {
+    t.before_swap(a, b);
+    let (na, nb) = (a.clone(), b.clone());
+    // 相邻时指向对方的链接要换成指向自己
+    let sub = |x:Option<L::Ptr>| match x {
+        Some(x) if x == na => Some(nb.clone()),
+        Some(x) if x == nb => Some(na.clone()),
+        x => x
+    };
+    let (ap, al, ar, ac) = (sub(t.parent(&na)), sub(t.left(&na)), sub(t.right(&na)), t.color(&na));
+    let (bp, bl, br, bc) = (sub(t.parent(&nb)), sub(t.left(&nb)), sub(t.right(&nb)), t.color(&nb));
+    // 兄弟节点只需交换父节点的左右子节点
+    let siblings = ap.is_some() && ap == bp;
+    if let Some(p) = ap.as_ref().filter(|_| siblings) {
+        let (l, r) = (t.left(p), t.right(p));
+        t.set_left(p, r);
+        t.set_right(p, l);
+    }
+    t.set_parent(&na, bp);
+    t.set_left(&na, bl);
+    t.set_right(&na, br);
+    t.set_color(&na, bc);
+    t.set_parent(&nb, ap);
+    t.set_left(&nb, al);
+    t.set_right(&nb, ar);
+    t.set_color(&nb, ac);
+    // 外部的父节点和子节点改为指向换过来的节点
+    for (n, other) in [(&na, &nb), (&nb, &na)] {
+        match t.parent(n) {
+            None => t.set_root(Some(n.clone())),
+            Some(p) if p != *other && !siblings => {
+                let right = t.right(&p).as_ref() == Some(other);
+                set_child(t, &p, right, Some(n.clone()));
+            },
+            Some(_) => {}
+        }
+        for c in [t.left(n), t.right(n)].into_iter().flatten() {
+            if c != *other {
+                t.set_parent(&c, Some(n.clone()));
+            }
+        }
+    }
+    mem::swap(a, b);
+}
+
+// 摘下叶子 x。摘下的节点不再有父节点，RedBlackTree 的句柄据此判断已被删除
+fn drop_node<L: Links>(t:&mut L, x:L::Ptr) {
+    t.before_unlink(&x);
+    match t.parent(&x) {
+        Some(p) => {
+            let right = t.right(&p).as_ref() == Some(&x);
+            set_child(t, &p, right, None);
+        },
+        None => t.set_root(None)
+    }
+    t.set_parent(&x, None);
+}
+
+fn root_fix<L: Links>(t:&mut L) {
+    if let Some(root) = t.root() {
+        recolor(t, &root, Color::Black);
+    }
+}
+
+pub(super) fn min<L: Links>(t:&L, mut x:L::Ptr) -> L::Ptr {
+    while let Some(l) = t.left(&x) {
+        x = l;
+    }
+    x
+}
+
+pub(super) fn max<L: Links>(t:&L, mut x:L::Ptr) -> L::Ptr {
+    while let Some(r) = t.right(&x) {
+        x = r;
+    }
+    x
+}
+
+pub(super) fn first<L: Links>(t:&L) -> Option<L::Ptr> {
+    t.root().map(|r| min(t, r))
+}
+
+pub(super) fn last<L: Links>(t:&L) -> Option<L::Ptr> {
+    t.root().map(|r| max(t, r))
+}
+
+// 中序后继
+pub(super) fn next<L: Links>(t:&L, x:&L::Ptr) -> Option<L::Ptr> {
+    if let Some(r) = t.right(x) {
+        return Some(min(t, r));
+    }
+    let mut x = x.clone();
+    loop {
+        let p = t.parent(&x)?;
+        if t.left(&p) == Some(x) {
+            return Some(p);
+        }
+        x = p;
+    }
+}
+
+// 中序前驱
+pub(super) fn prev<L: Links>(t:&L, x:&L::Ptr) -> Option<L::Ptr> {
+    if let Some(l) = t.left(x) {
+        return Some(max(t, l));
+    }
+    let mut x = x.clone();
+    loop {
+        let p = t.parent(&x)?;
+        if t.right(&p) == Some(x) {
+            return Some(p);
+        }
+        x = p;
+    }
+}
+
+/// 能按键比较的树：节点的键和比较器。键通过闭包借出，RedBlackTree 的键在 RefCell 中
+pub(super) trait Keys: Links {
+    type Key;
+    type Compare;
+    fn with_key<R>(&self, x:&Self::Ptr, f:impl FnOnce(&Self::Key) -> R) -> R;
+    fn comparator(&self) -> &Self::Compare;
+    // 每次比较前调用，用于计数
+    fn before_compare(&self) {}
+}
+
+fn compare<L: Keys, Q: ?Sized>(t:&L, x:&L::Ptr, value:&Q) -> Ordering
+where L::Key: Borrow<Q>, L::Compare: Compare<Q> {
+    t.before_compare();
+    t.with_key(x, |k| t.comparator().compare(k.borrow(), value))
+}
+
+/// 二分查找。找到返回 Ok(x)，否则返回 Err(插入位置)：(父节点, 是否为右子节点)，空树为 None
+pub(super) fn find<L: Keys, Q: ?Sized>(t:&L, value:&Q) -> Result<L::Ptr, Option<(L::Ptr, bool)>>
+where L::Key: Borrow<Q>, L::Compare: Compare<Q> {
+    let mut slot = None;
+    let mut x = t.root();
+    while let Some(n) = x {
+        t.before_compare();
+        match t.with_key(&n, |k| t.comparator().compare(value, k.borrow())) {
+            Ordering::Equal => return Ok(n),
+            Ordering::Less => {
+                x = t.left(&n);
+                slot = Some((n, false));
+            },
+            Ordering::Greater => {
+                x = t.right(&n);
+                slot = Some((n, true));
+            }
+        }
+    }
+    Err(slot)
+}
+
+/// 第一个满足下界的节点
+pub(super) fn lower_bound<L: Keys, Q: ?Sized>(t:&L, bound:Bound<&Q>) -> Option<L::Ptr>
+where L::Key: Borrow<Q>, L::Compare: Compare<Q> {
+    let mut found = None;
+    let mut x = t.root();
+    while let Some(n) = x {
+        let hit = match bound {
+            Bound::Included(v) => compare(t, &n, v) != Ordering::Less,
+            Bound::Excluded(v) => compare(t, &n, v) == Ordering::Greater,
+            Bound::Unbounded => true
+        };
+        if hit {
+            x = t.left(&n);
+            found = Some(n);
+        } else {
+            x = t.right(&n);
+        }
+    }
+    found
+}
+
+/// 小于等于 value 的最大节点
+pub(super) fn floor<L: Keys, Q: ?Sized>(t:&L, value:&Q) -> Option<L::Ptr>
+where L::Key: Borrow<Q>, L::Compare: Compare<Q> {
+    match lower_bound(t, Bound::Excluded(value)) {
+        Some(x) => prev(t, &x),
+        None => last(t)
+    }
+}
+
+/// 大于等于 value 的最小节点
+pub(super) fn ceiling<L: Keys, Q: ?Sized>(t:&L, value:&Q) -> Option<L::Ptr>
+where L::Key: Borrow<Q>, L::Compare: Compare<Q> {
+    lower_bound(t, Bound::Included(value))
+}
+
+/// 范围内的节点，下界大于上界时为空
+pub(super) fn range<L: Keys, Q: ?Sized, R: RangeBounds<Q>>(t:&L, range:R) -> Cursor<L::Ptr>
+where L::Key: Borrow<Q>, L::Compare: Compare<Q> {
+    let start = lower_bound(t, range.start_bound());
+    // 第一个超出上界的节点
+    let end = match range.end_bound() {
+        Bound::Included(v) => lower_bound(t, Bound::Excluded(v)),
+        Bound::Excluded(v) => lower_bound(t, Bound::Included(v)),
+        Bound::Unbounded => None
+    };
+    if let (Some(s), Some(e)) = (&start, &end) {
+        if t.with_key(e, |k| compare(t, s, k.borrow())) == Ordering::Greater {
+            return Cursor { next: None, end: None };
+        }
+    }
+    Cursor { next: start, end }
+}
+
+/// 按键摘下节点并返回，调用者负责 len 和释放
+pub(super) fn take<L: Keys, Q: ?Sized>(t:&mut L, value:&Q) -> Option<L::Ptr>
+where L::Key: Borrow<Q>, L::Compare: Compare<Q> {
+    let z = find(t, value).ok()?;
+    delete(t, z.clone());
+    Some(z)
+}
+
+/// 中序遍历的位置，`end` 为第一个不再返回的节点
+pub(super) struct Cursor<P> {
+    next: Option<P>,
+    end: Option<P>
+}
+
+impl<P: Clone + Eq> Cursor<P> {
+    pub(super) fn all<L: Links<Ptr = P>>(t:&L) -> Self {
+        Cursor { next: first(t), end: None }
+    }
+    // (第一个节点, 第一个不再返回的节点)
+    pub(super) fn into_bounds(self) -> (Option<P>, Option<P>) {
+        (self.next, self.end)
+    }
+    pub(super) fn step<L: Links<Ptr = P>>(&mut self, t:&L) -> Option<P> {
+        let x = self.next.take().filter(|x| Some(x) != self.end.as_ref())?;
+        self.next = next(t, &x);
+        Some(x)
+    }
+}
+
+/// 把新节点 x 挂到 slot 指定的位置 (由 find 返回) 并修复。空树时 x 成为黑色的根
+pub(super) fn insert_at<L: Links>(t:&mut L, x:L::Ptr, slot:Option<(L::Ptr, bool)>) {
+    t.set_left(&x, None);
+    t.set_right(&x, None);
+    match slot {
+        None => {
+            t.set_color(&x, Color::Black);
+            t.set_parent(&x, None);
+            t.set_root(Some(x));
+        },
+        Some((p, right)) => {
+            t.set_color(&x, Color::Red);
+            t.set_parent(&x, Some(p.clone()));
+            set_child(t, &p, right, Some(x.clone()));
+            insert_fix(t, x);
+        }
+    }
+}
+
+fn insert_fix<L: Links>(t:&mut L, x:L::Ptr) {
+    let mut x = x;
+    loop {
+        t.case(Case::InsertFixLoop);
+        if t.color(&x) == Color::Red {
+            if let Some(mut p) = t.parent(&x) {
+                if t.color(&p) == Color::Red {
+                    if let Some(g) = t.parent(&p) {
+                        if t.color(&g) == Color::Black {
+                            let u = brother(t, &p);
+                            let uncle = u.as_ref().map(|u| t.color(u));
+                            let (p_right, x_right) = (is_right(t, &p), is_right(t, &x));
+                            t.case(Case::InsertFix { uncle, p_right, x_right });
+                            // 不能判断U，U可能不存在，用P判断
+                            if p_right {
+                                if !x_right {
+                                    right_rotate(t, &x);
+                                } else {
+                                    mem::swap(&mut x, &mut p);
+                                }
+                                left_rotate(t, &x);
+                            } else {
+                                if x_right {
+                                    left_rotate(t, &x);
+                                } else {
+                                    mem::swap(&mut x, &mut p);
+                                }
+                                right_rotate(t, &x);
+                            }
+                            match u {
+                                Some(u) if t.color(&u) == Color::Red => recolor(t, &p, Color::Black),
+                                Some(_) => {
+                                    recolor(t, &x, Color::Black);
+                                    recolor(t, &g, Color::Red);
+                                },
+                                None => recolor(t, &p, Color::Black)
+                            }
+                            continue;
+                        }
+                    }
+                }
+            }
+        }
+        break;
+    }
+    root_fix(t);
+}
+
+// 有两个子节点时的替身：右子树的最小节点，没有右子树时取左子树的最大节点
+fn delete_search<L: Links>(t:&L, x:&L::Ptr) -> Option<L::Ptr> {
+    if let Some(r) = t.right(x) {
+        Some(min(t, r))
+    } else {
+        t.left(x).map(|l| max(t, l))
+    }
+}
+
+/// 把 x 从树中摘下并修复，x 的内存和 len 由调用者处理。
+/// 调整时交换节点位置 (而不是交换值)，所以其他节点的 Ptr 一直指向同一个键
+pub(super) fn delete<L: Links>(t:&mut L, x:L::Ptr) {
+    let mut x = x;
+    if let Some(mut pv) = delete_search(t, &x) {
+        t.case(Case::Successor);
+        swap_nodes(t, &mut x, &mut pv);
+        x = pv;
+    }
+    loop {
+        match t.color(&x) {
+            // 规则1 X红
+            Color::Red => {
+                t.case(Case::Del(1));
+                drop_node(t, x);
+            },
+            Color::Black => {
+                // 规则2 X黑 无父无子
+                if t.parent(&x).is_none() && t.left(&x).is_none() && t.right(&x).is_none() {
+                    t.case(Case::Del(2));
+                    drop_node(t, x);
+                } else if let Some(mut c) = t.left(&x).or_else(|| t.right(&x)) {
+                    // 规则3 X黑有一个子节点
+                    t.case(Case::Del(3));
+                    swap_nodes(t, &mut x, &mut c);
+                    x = c;
+                    continue;
+                } else if let (Some(mut p), Some(mut b)) = (t.parent(&x), brother(t, &x)) {
+                    match t.color(&p) {
+                        Color::Red => {
+                            if t.color(&b) != Color::Black {
+                                panic!("节点不平衡");
+                            }
+                            //规则4 X黑 P红 B黑
+                            t.case(Case::Del(4));
+                            let (c_a, c_b);
+                            if is_left(t, &x) {
+                                c_a = t.left(&b);
+                                c_b = t.right(&b);
+                            } else {
+                                c_a = t.right(&b);
+                                c_b = t.left(&b);
+                            }
+                            if let Some(mut c) = c_a {
+                                if t.color(&c) != Color::Red {
+                                    panic!("节点不平衡");
+                                }
+                                swap_nodes(t, &mut x, &mut p);
+                                swap_nodes(t, &mut p, &mut c);
+                                x = c;
+                                continue;
+                            } else if let Some(mut c) = c_b {
+                                if t.color(&c) != Color::Red {
+                                    panic!("节点不平衡");
+                                }
+                                swap_nodes(t, &mut x, &mut p);
+                                swap_nodes(t, &mut p, &mut b);
+                                swap_nodes(t, &mut b, &mut c);
+                                x = c;
+                                continue;
+                            } else {
+                                recolor(t, &p, Color::Black);
+                                recolor(t, &b, Color::Red);
+                                drop_node(t, x);
+                            }
+                        },
+                        Color::Black => {
+                            match t.color(&b) {
+                                Color::Red => {
+                                    //规则5 X黑 P黑 B红
+                                    t.case(Case::Del(5));
+                                    let c = if is_left(t, &x) {
+                                        t.left(&b)
+                                    } else {
+                                        t.right(&b)
+                                    };
+                                    let Some(mut c) = c else {
+                                        panic!("节点不平衡");
+                                    };
+                                    if t.color(&c) != Color::Black {
+                                        panic!("节点不平衡");
+                                    }
+                                    // C可能有红子节点
+                                    let (c_a, c_b);
+                                    if is_left(t, &x) {
+                                        c_a = t.left(&c);
+                                        c_b = t.right(&c);
+                                    } else {
+                                        c_a = t.right(&c);
+                                        c_b = t.left(&c);
+                                    }
+                                    if let Some(mut c_a) = c_a {
+                                        swap_nodes(t, &mut x, &mut p);
+                                        swap_nodes(t, &mut p, &mut c_a);
+                                        x = c_a;
+                                        // 转到规则1
+                                        continue;
+                                    } else if let Some(mut c_b) = c_b {
+                                        swap_nodes(t, &mut x, &mut p);
+                                        swap_nodes(t, &mut p, &mut c);
+                                        swap_nodes(t, &mut c, &mut c_b);
+                                        x = c_b;
+                                        // 转到规则1
+                                        continue;
+                                    } else {
+                                        swap_nodes(t, &mut x, &mut p);
+                                        swap_nodes(t, &mut p, &mut c);
+                                        x = c;
+                                        // 转到规则4
+                                        continue;
+                                    }
+                                },
+                                Color::Black => {
+                                    //规则6 X黑 P黑 B黑
+                                    t.case(Case::Del(6));
+                                    let (c_a, c_b);
+                                    if is_left(t, &x) {
+                                        c_a = t.left(&b);
+                                        c_b = t.right(&b);
+                                    } else {
+                                        c_a = t.right(&b);
+                                        c_b = t.left(&b);
+                                    }
+                                    if let Some(mut c) = c_a {
+                                        if t.color(&c) != Color::Red {
+                                            panic!("节点不平衡");
+                                        }
+                                        swap_nodes(t, &mut x, &mut p);
+                                        swap_nodes(t, &mut p, &mut c);
+                                        x = c;
+                                        continue;
+                                    } else if let Some(mut c) = c_b {
+                                        if t.color(&c) != Color::Red {
+                                            panic!("节点不平衡");
+                                        }
+                                        swap_nodes(t, &mut x, &mut p);
+                                        swap_nodes(t, &mut p, &mut b);
+                                        swap_nodes(t, &mut b, &mut c);
+                                        x = c;
+                                        continue;
+                                    }
+                                    /* 无子节点 */
+                                    recolor(t, &p, Color::Black);
+                                    recolor(t, &b, Color::Red);
+                                    recolor(t, &x, Color::Red);
+                                    // P无父
+                                    if t.parent(&p).is_none() {
+                                        drop_node(t, x);
+                                        break;
+                                    }
+                                    // P有父，P和X不在同一侧时先把B换到P的位置
+                                    if is_left(t, &x) == is_right(t, &p) {
+                                        swap_nodes(t, &mut x, &mut b);
+                                        mem::swap(&mut x, &mut b);
+                                        swap_nodes(t, &mut b, &mut p);
+                                    }
+                                    drop_node(t, x);
+                                    delete_fix(t, p);
+                                }
+                            }
+                        }
+                    }
+                } else {
+                    panic!("节点不平衡");
+                }
+            }
+        }
+        break;
+    }
+    root_fix(t);
+}
+
+// x 为黑且还在树中，x 所在的路径少一个黑节点
+fn delete_fix<L: Links>(t:&mut L, x:L::Ptr) {
+    let mut x = x;
+    loop {
+        if t.color(&x) == Color::Black {
+            if let (Some(p), Some(b)) = (t.parent(&x), brother(t, &x)) {
+                let x_right = is_right(t, &x);
+                match t.color(&p) {
+                    Color::Red => {
+                        let (cl, cr) = if x_right {
+                            (t.left(&b).unwrap(), t.right(&b).unwrap())
+                        } else {
+                            (t.right(&b).unwrap(), t.left(&b).unwrap())
+                        };
+                        match (t.color(&cl), t.color(&cr)) {
+                            (Color::Black, Color::Black) => {
+                                //规则1 P红 B黑 双C黑
+                                t.case(Case::DeleteFix(1));
+                                recolor(t, &p, Color::Black);
+                                recolor(t, &b, Color::Red);
+                            },
+                            (Color::Red, Color::Red) => {
+                                //规则2 P红 B黑 双C红
+                                t.case(Case::DeleteFix(2));
+                                if x_right {
+                                    right_rotate(t, &b);
+                                } else {
+                                    left_rotate(t, &b);
+                                }
+                                recolor(t, &cl, Color::Black);
+                                recolor(t, &p, Color::Black);
+                                recolor(t, &b, Color::Red);
+                            },
+                            (Color::Red, Color::Black) => {
+                                //规则3 P红 B黑 CL红 CR黑
+                                t.case(Case::DeleteFix(3));
+                                if x_right {
+                                    right_rotate(t, &b);
+                                } else {
+                                    left_rotate(t, &b);
+                                }
+                            },
+                            (Color::Black, Color::Red) => {
+                                //规则4 P红 B黑 CL黑 CR红
+                                t.case(Case::DeleteFix(4));
+                                if x_right {
+                                    left_rotate(t, &cr);
+                                    right_rotate(t, &cr);
+                                } else {
+                                    right_rotate(t, &cr);
+                                    left_rotate(t, &cr);
+                                }
+                                recolor(t, &b, Color::Red);
+                                recolor(t, &cr, Color::Black);
+                            }
+                        }
+                    },
+                    Color::Black => {
+                        match t.color(&b) {
+                            Color::Red => {
+                                let (c, l, r);
+                                if x_right {
+                                    c = t.right(&b).unwrap();
+                                    l = t.left(&c).unwrap();
+                                    r = t.right(&c).unwrap();
+                                } else {
+                                    c = t.left(&b).unwrap();
+                                    l = t.right(&c).unwrap();
+                                    r = t.left(&c).unwrap();
+                                }
+                                match (t.color(&l), t.color(&r)) {
+                                    (Color::Black, Color::Black) => {
+                                        //规则5 P黑 B红 C双子(双黑)
+                                        t.case(Case::DeleteFix(5));
+                                        if x_right {
+                                            right_rotate(t, &b);
+                                        } else {
+                                            left_rotate(t, &b);
+                                        }
+                                        recolor(t, &b, Color::Black);
+                                        recolor(t, &c, Color::Red);
+                                    },
+                                    (Color::Red, Color::Red) => {
+                                        //规则6 P黑 B红 C双子(双红)
+                                        t.case(Case::DeleteFix(6));
+                                        if x_right {
+                                            right_rotate(t, &b);
+                                            right_rotate(t, &c);
+                                            left_rotate(t, &c);
+                                        } else {
+                                            left_rotate(t, &b);
+                                            left_rotate(t, &c);
+                                            right_rotate(t, &c);
+                                        }
+                                        recolor(t, &l, Color::Black);
+                                    },
+                                    (Color::Red, Color::Black) => {
+                                        //规则7 P黑 B红 C双子(左红右黑)
+                                        t.case(Case::DeleteFix(7));
+                                        if x_right {
+                                            right_rotate(t, &b);
+                                            right_rotate(t, &c);
+                                            left_rotate(t, &c);
+                                        } else {
+                                            left_rotate(t, &b);
+                                            left_rotate(t, &c);
+                                            right_rotate(t, &c);
+                                        }
+                                        recolor(t, &l, Color::Black);
+                                    },
+                                    (Color::Black, Color::Red) => {
+                                        //规则8 P黑 B红 C双子(左黑右红)
+                                        t.case(Case::DeleteFix(8));
+                                        if x_right {
+                                            right_rotate(t, &b);
+                                            left_rotate(t, &r);
+                                            right_rotate(t, &r);
+                                        } else {
+                                            left_rotate(t, &b);
+                                            right_rotate(t, &r);
+                                            left_rotate(t, &r);
+                                        }
+                                        recolor(t, &b, Color::Black);
+                                    }
+                                }
+                            },
+                            Color::Black => {
+                                let (cl, cr) = if x_right {
+                                    (t.left(&b).unwrap(), t.right(&b).unwrap())
+                                } else {
+                                    (t.right(&b).unwrap(), t.left(&b).unwrap())
+                                };
+                                match (t.color(&cl), t.color(&cr)) {
+                                    (Color::Black, Color::Black) => {
+                                        //规则9 P黑 B黑 双C黑(递归P)
+                                        t.case(Case::DeleteFix(9));
+                                        recolor(t, &b, Color::Red);
+                                        x = p;
+                                        continue;
+                                    },
+                                    (Color::Red, Color::Red) => {
+                                        //规则10 P黑 B黑 双C红
+                                        t.case(Case::DeleteFix(10));
+                                        if x_right {
+                                            right_rotate(t, &b);
+                                        } else {
+                                            left_rotate(t, &b);
+                                        }
+                                        recolor(t, &cl, Color::Black);
+                                    },
+                                    (Color::Black, Color::Red) => {
+                                        //规则11 P黑 B黑 CL黑 CR红
+                                        t.case(Case::DeleteFix(11));
+                                        if x_right {
+                                            left_rotate(t, &cr);
+                                            right_rotate(t, &cr);
+                                        } else {
+                                            right_rotate(t, &cr);
+                                            left_rotate(t, &cr);
+                                        }
+                                        recolor(t, &cr, Color::Black);
+                                    },
+                                    (Color::Red, Color::Black) => {
+                                        //规则12 P黑 B黑 CL红 CR黑
+                                        t.case(Case::DeleteFix(12));
+                                        if x_right {
+                                            right_rotate(t, &b);
+                                        } else {
+                                            left_rotate(t, &b);
+                                        }
+                                        recolor(t, &cl, Color::Black);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        break;
+    }
+}
+
+/// 检查红黑树性质和键的顺序
+pub(super) fn validate<L: Keys>(t:&L, len:usize) -> Result<(), InvariantError>
+where L::Compare: Compare<L::Key> {
+    let Some(root) = t.root() else {
+        return match len {
+            0 => Ok(()),
+            n => Err(InvariantError::Len { recorded: n, actual: 0 })
+        };
+    };
+    if t.color(&root) != Color::Black {
+        return Err(InvariantError::RootNotBlack);
+    }
+    if t.parent(&root).is_some() {
+        return Err(InvariantError::BrokenParent);
+    }
+    let mut count = 0;
+    let mut black_height = None;
+    // (节点, 路径上的黑节点数)
+    let mut stack = vec![(root, 0)];
+    while let Some((x, blacks)) = stack.pop() {
+        count += 1;
+        if count > len {
+            // 链接成环时不会结束
+            return Err(InvariantError::Len { recorded: len, actual: count });
+        }
+        let blacks = blacks + (t.color(&x) == Color::Black) as usize;
+        for right in [false, true] {
+            match child(t, &x, right) {
+                None => {
+                    if *black_height.get_or_insert(blacks) != blacks {
+                        return Err(InvariantError::BlackHeight);
+                    }
+                },
+                Some(c) => {
+                    if t.color(&x) == Color::Red && t.color(&c) == Color::Red {
+                        return Err(InvariantError::RedRed);
+                    }
+                    if t.parent(&c).as_ref() != Some(&x) {
+                        return Err(InvariantError::BrokenParent);
+                    }
+                    stack.push((c, blacks));
+                }
+            }
+        }
+    }
+    if count != len {
+        return Err(InvariantError::Len { recorded: len, actual: count });
+    }
+    let mut x = first(t);
+    while let Some(n) = x {
+        x = next(t, &n);
+        if let Some(m) = &x {
+            if t.with_key(m, |k| compare(t, &n, k)) != Ordering::Less {
+                return Err(InvariantError::Order);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use core::cell::RefCell;
+use alloc::rc::{Rc, Weak};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::{Color, Node, NodeRef, RedBlackTree};
+
+// 用有序节点重建平衡树，最深一层染红，其余为黑
+fn build<K>(nodes:&[NodeRef<K>], parent:Option<Weak<RefCell<Node<K>>>>, depth:u32, red_depth:u32) -> Option<NodeRef<K>> {
+    if nodes.is_empty() {
+        return None;
+    }
+    let mid = nodes.len() / 2;
+    let x = nodes[mid].clone();
+    let left = build(&nodes[..mid], Some(Rc::downgrade(&x)), depth + 1, red_depth);
+    let right = build(&nodes[mid + 1..], Some(Rc::downgrade(&x)), depth + 1, red_depth);
+    {
+        let mut x_write = x.as_ref().borrow_mut();
+        x_write.left = left;
+        x_write.right = right;
+        x_write.parent = parent;
+        x_write.color = if depth > 0 && depth == red_depth { Color::Red } else { Color::Black };
+    }
+    Some(x)
+}
+
+// 拆下的节点没有子节点和父节点，取出其中的键。节点还被 get / Handle 持有时只留下空节点
+fn into_value<K>(x:NodeRef<K>) -> K {
+    x.as_ref().borrow_mut().value.take().expect("node in the tree always holds a key")
+}
+
+impl<K, C> RedBlackTree<K, C> {
+    // 按中序拆下所有节点，节点之间不再有连接，也不再属于这棵树
+    fn take_nodes(&mut self) -> Vec<NodeRef<K>> {
+        let mut nodes = Vec::with_capacity(self.len);
+        let mut stack = vec![];
+        let mut x = self.root.take();
+        loop {
+            while let Some(n) = x {
+                x = n.as_ref().borrow_mut().left.take();
+                stack.push(n);
+            }
+            match stack.pop() {
+                None => break,
+                Some(n) => {
+                    {
+                        let mut n_write = n.as_ref().borrow_mut();
+                        x = n_write.right.take();
+                        n_write.parent = None;
+                        n_write.owner = 0;
+                    }
+                    nodes.push(n);
+                }
+            }
+        }
+        self.len = 0;
+        nodes
+    }
+    fn rebuild(&mut self, nodes:Vec<NodeRef<K>>) {
+        for x in &nodes {
+            x.as_ref().borrow_mut().owner = self.id;
+        }
+        self.len = nodes.len();
+        self.root = match nodes.len() {
+            0 => None,
+            n => build(&nodes, None, 0, n.ilog2())
+        };
+    }
+    pub fn retain<F: FnMut(&K) -> bool>(&mut self, mut f:F) {
+        let (kept, removed): (Vec<_>, Vec<_>) = self.take_nodes()
+            .into_iter()
+            .partition(|x| f(x.as_ref().borrow().value()));
+        for x in &removed {
+            self.notify_remove(x);
+        }
+        self.rebuild(kept);
+    }
+    pub fn extract_if<F: FnMut(&K) -> bool>(&mut self, pred:F) -> ExtractIf<'_, K, C, F> {
+        let nodes = self.take_nodes();
+        ExtractIf {
+            kept: Vec::with_capacity(nodes.len()),
+            nodes: nodes.into_iter(),
+            tree: self,
+            pred
+        }
+    }
+    pub fn drain(&mut self) -> Drain<K> {
+        let nodes = self.take_nodes();
+        for x in &nodes {
+            self.notify_remove(x);
+        }
+        Drain {
+            nodes: nodes.into_iter()
+        }
+    }
+}
+
+/// `drain` 的返回值，按中序返回移除的键
+pub struct Drain<K> {
+    nodes: vec::IntoIter<NodeRef<K>>
+}
+
+impl<K> Iterator for Drain<K> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(into_value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+/// `extract_if` 的返回值，按中序返回满足条件的键，丢弃时用未取出的节点重建树
+pub struct ExtractIf<'a, K, C, F: FnMut(&K) -> bool> {
+    tree: &'a mut RedBlackTree<K, C>,
+    nodes: vec::IntoIter<NodeRef<K>>,
+    kept: Vec<NodeRef<K>>,
+    pred: F
+}
+
+impl<'a, K, C, F: FnMut(&K) -> bool> Iterator for ExtractIf<'a, K, C, F> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for x in self.nodes.by_ref() {
+            if (self.pred)(x.as_ref().borrow().value()) {
+                self.tree.notify_remove(&x);
+                return Some(into_value(x));
+            }
+            self.kept.push(x);
+        }
+        None
+    }
+}
+
+impl<'a, K, C, F: FnMut(&K) -> bool> Drop for ExtractIf<'a, K, C, F> {
+    fn drop(&mut self) {
+        let mut kept = core::mem::take(&mut self.kept);
+        kept.extend(self.nodes.by_ref());
+        self.tree.rebuild(kept);
+    }
+}
@@ -0,0 +1,261 @@
+use core::borrow::Borrow;
+use core::fmt;
+use core::mem::size_of;
+use core::ops::RangeBounds;
+use alloc::vec::Vec;
+
+use super::balance::{self, Keys, Links};
+use super::{Color, Compare, InvariantError, MemoryUsage, Natural};
+
+// 父索引的最高位存颜色，剩下 31 位存索引
+const RED: u32 = 1 << 31;
+const NIL: u32 = RED - 1;
+
+#[derive(Clone)]
+struct CompactNode<K> {
+    left: u32,
+    right: u32,
+    parent_color: u32,
+    value: K
+}
+
+fn to_ptr(i:u32) -> Option<u32> {
+    if i == NIL { None } else { Some(i) }
+}
+
+fn to_index(x:Option<u32>) -> u32 {
+    x.unwrap_or(NIL)
+}
+
+/// 紧凑布局的红黑树：节点连续存放在一个 Vec 中，用 32 位索引链接，颜色放在父索引的最高位，
+/// 每个节点只比 K 多 12 字节。删除时把最后一个节点搬到空出的位置，所以不返回节点，只返回 &K。
+/// 最多存放 2^31 - 1 个键
+#[derive(Clone)]
+pub struct CompactRedBlackTree<K, C = Natural> {
+    nodes: Vec<CompactNode<K>>,
+    root: u32,
+    compare: C
+}
+
+impl<K, C> Links for CompactRedBlackTree<K, C> {
+    type Ptr = u32;
+    fn root(&self) -> Option<u32> {
+        to_ptr(self.root)
+    }
+    fn set_root(&mut self, x:Option<u32>) {
+        self.root = to_index(x);
+    }
+    fn left(&self, x:&u32) -> Option<u32> {
+        to_ptr(self.nodes[*x as usize].left)
+    }
+    fn right(&self, x:&u32) -> Option<u32> {
+        to_ptr(self.nodes[*x as usize].right)
+    }
+    fn parent(&self, x:&u32) -> Option<u32> {
+        to_ptr(self.nodes[*x as usize].parent_color & NIL)
+    }
+    fn color(&self, x:&u32) -> Color {
+        if self.nodes[*x as usize].parent_color & RED != 0 { Color::Red } else { Color::Black }
+    }
+    fn set_left(&mut self, x:&u32, c:Option<u32>) {
+        self.nodes[*x as usize].left = to_index(c);
+    }
+    fn set_right(&mut self, x:&u32, c:Option<u32>) {
+        self.nodes[*x as usize].right = to_index(c);
+    }
+    fn set_parent(&mut self, x:&u32, p:Option<u32>) {
+        let n = &mut self.nodes[*x as usize];
+        n.parent_color = (n.parent_color & RED) | to_index(p);
+    }
+    fn set_color(&mut self, x:&u32, color:Color) {
+        let n = &mut self.nodes[*x as usize];
+        match color {
+            Color::Red => n.parent_color |= RED,
+            Color::Black => n.parent_color &= NIL
+        }
+    }
+}
+
+impl<K, C> Keys for CompactRedBlackTree<K, C> {
+    type Key = K;
+    type Compare = C;
+    fn with_key<R>(&self, x:&u32, f:impl FnOnce(&K) -> R) -> R {
+        f(self.value(*x))
+    }
+    fn comparator(&self) -> &C {
+        &self.compare
+    }
+}
+
+impl<K> CompactRedBlackTree<K> {
+    pub fn new() -> Self {
+        Self::with_comparator(Natural)
+    }
+}
+
+impl<K, C> CompactRedBlackTree<K, C> {
+    pub fn with_comparator(compare:C) -> Self {
+        Self {
+            nodes: Vec::new(),
+            root: NIL,
+            compare
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.root = NIL;
+    }
+    pub fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+    }
+    fn value(&self, x:u32) -> &K {
+        &self.nodes[x as usize].value
+    }
+    pub fn iter(&self) -> CompactIter<'_, K, C> {
+        CompactIter { tree: self, cursor: balance::Cursor::all(self) }
+    }
+    pub fn first(&self) -> Option<&K> {
+        balance::first(self).map(|x| self.value(x))
+    }
+    pub fn last(&self) -> Option<&K> {
+        balance::last(self).map(|x| self.value(x))
+    }
+    // Vec 按容量分配，所以总量按容量计算
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let node_bytes = size_of::<CompactNode<K>>();
+        let value_bytes = size_of::<K>();
+        let link_bytes = 3 * size_of::<u32>();
+        let tree_bytes = size_of::<Self>();
+        MemoryUsage {
+            len: self.len(),
+            node_bytes,
+            value_bytes,
+            rc_header_bytes: 0,
+            refcell_flag_bytes: 0,
+            link_bytes,
+            color_bytes: 0,
+            padding_bytes: node_bytes - link_bytes - value_bytes,
+            tree_bytes,
+            total_bytes: tree_bytes + self.nodes.capacity() * node_bytes
+        }
+    }
+}
+
+impl<K, C: Compare<K>> CompactRedBlackTree<K, C> {
+    pub fn add(&mut self, value:K) {
+        match balance::find(self, &value) {
+            // 比较器认为相等的键直接替换
+            Ok(x) => self.nodes[x as usize].value = value,
+            Err(slot) => {
+                let x = self.nodes.len() as u32;
+                assert!(x < NIL, "CompactRedBlackTree is limited to {} keys", NIL);
+                self.nodes.push(CompactNode { left: NIL, right: NIL, parent_color: NIL, value });
+                balance::insert_at(self, x, slot);
+            }
+        }
+    }
+    pub fn get<Q: ?Sized>(&self, value:&Q) -> Option<&K>
+    where K: Borrow<Q>, C: Compare<Q> {
+        balance::find(self, value).ok().map(|x| self.value(x))
+    }
+    pub fn contains<Q: ?Sized>(&self, value:&Q) -> bool
+    where K: Borrow<Q>, C: Compare<Q> {
+        balance::find(self, value).is_ok()
+    }
+    // 小于等于 value 的最大键
+    pub fn floor<Q: ?Sized>(&self, value:&Q) -> Option<&K>
+    where K: Borrow<Q>, C: Compare<Q> {
+        balance::floor(self, value).map(|x| self.value(x))
+    }
+    // 大于等于 value 的最小键
+    pub fn ceiling<Q: ?Sized>(&self, value:&Q) -> Option<&K>
+    where K: Borrow<Q>, C: Compare<Q> {
+        balance::ceiling(self, value).map(|x| self.value(x))
+    }
+    pub fn range<Q: ?Sized, R: RangeBounds<Q>>(&self, range:R) -> CompactIter<'_, K, C>
+    where K: Borrow<Q>, C: Compare<Q> {
+        CompactIter { tree: self, cursor: balance::range(self, range) }
+    }
+    pub fn take<Q: ?Sized>(&mut self, value:&Q) -> Option<K>
+    where K: Borrow<Q>, C: Compare<Q> {
+        let z = balance::take(self, value)?;
+        // 把最后一个节点搬到 z 的位置，修正指向它的链接
+        let last = self.nodes.len() as u32 - 1;
+        let removed = self.nodes.swap_remove(z as usize);
+        if z != last {
+            let p = self.parent(&z);
+            match p {
+                None => self.root = z,
+                Some(p) if self.nodes[p as usize].left == last => self.nodes[p as usize].left = z,
+                Some(p) => self.nodes[p as usize].right = z
+            }
+            for c in [self.left(&z), self.right(&z)].into_iter().flatten() {
+                self.set_parent(&c, Some(z));
+            }
+        }
+        Some(removed.value)
+    }
+    pub fn remove<Q: ?Sized>(&mut self, value:&Q) -> bool
+    where K: Borrow<Q>, C: Compare<Q> {
+        self.take(value).is_some()
+    }
+    pub fn del<Q: ?Sized>(&mut self, value:&Q)
+    where K: Borrow<Q>, C: Compare<Q> {
+        self.remove(value);
+    }
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        balance::validate(self, self.len())
+    }
+    pub fn check_tree(&self) -> bool {
+        self.validate().is_ok()
+    }
+}
+
+/// 按中序遍历键
+pub struct CompactIter<'a, K, C> {
+    tree: &'a CompactRedBlackTree<K, C>,
+    cursor: balance::Cursor<u32>
+}
+
+impl<'a, K, C> Iterator for CompactIter<'a, K, C> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let x = self.cursor.step(self.tree)?;
+        Some(self.tree.value(x))
+    }
+}
+
+impl<K, C: Default> Default for CompactRedBlackTree<K, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<K, C: Compare<K> + Default> FromIterator<K> for CompactRedBlackTree<K, C> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter:I) -> Self {
+        let mut tree = Self::default();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K, C: Compare<K>> Extend<K> for CompactRedBlackTree<K, C> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter:I) {
+        for value in iter {
+            self.add(value);
+        }
+    }
+}
+
+impl<K: fmt::Debug, C> fmt::Debug for CompactRedBlackTree<K, C> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,42 @@
+use core::cmp::Ordering;
+
+/// 键的比较器，`RedBlackTree` 中所有的大小判断都经过它
+pub trait Compare<K: ?Sized> {
+    fn compare(&self, a:&K, b:&K) -> Ordering;
+}
+
+/// 默认比较器，直接使用 `Ord`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Natural;
+
+impl<K: Ord + ?Sized> Compare<K> for Natural {
+    fn compare(&self, a:&K, b:&K) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+/// 反转内部比较器的顺序
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Reverse<C = Natural>(pub C);
+
+impl<K: ?Sized, C: Compare<K>> Compare<K> for Reverse<C> {
+    fn compare(&self, a:&K, b:&K) -> Ordering {
+        self.0.compare(b, a)
+    }
+}
+
+/// 按 `f(key)` 的结果排序，例如 `ByKey(|p: &Person| p.age)`
+#[derive(Debug, Clone, Copy)]
+pub struct ByKey<F>(pub F);
+
+impl<K: ?Sized, T: Ord, F: Fn(&K) -> T> Compare<K> for ByKey<F> {
+    fn compare(&self, a:&K, b:&K) -> Ordering {
+        (self.0)(a).cmp(&(self.0)(b))
+    }
+}
+
+impl<K: ?Sized, F: Fn(&K, &K) -> Ordering> Compare<K> for F {
+    fn compare(&self, a:&K, b:&K) -> Ordering {
+        self(a, b)
+    }
+}
@@ -0,0 +1,39 @@
+#[cfg(feature = "instrument")]
+use core::cell::RefCell;
+
+/// 调整过程的计数，`del_case[i]` 对应 `del` 的规则 i+1，`delete_fix_case[i]` 对应 `delete_fix` 的规则 i+1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CounterSnapshot {
+    pub left_rotate: u64,
+    pub right_rotate: u64,
+    pub recolor: u64,
+    pub insert_fix_loop: u64,
+    pub del_case: [u64; 6],
+    pub delete_fix_case: [u64; 12],
+    pub compare: u64
+}
+
+// 未开启 instrument 时为空结构体，bump 不做任何事
+#[derive(Debug, Clone, Default)]
+pub(super) struct Counters {
+    #[cfg(feature = "instrument")]
+    inner: RefCell<CounterSnapshot>
+}
+
+impl Counters {
+    #[inline(always)]
+    pub(super) fn bump(&self, f:impl FnOnce(&mut CounterSnapshot)) {
+        #[cfg(feature = "instrument")]
+        f(&mut self.inner.borrow_mut());
+        #[cfg(not(feature = "instrument"))]
+        let _ = f;
+    }
+    #[cfg(feature = "instrument")]
+    pub(super) fn snapshot(&self) -> CounterSnapshot {
+        *self.inner.borrow()
+    }
+    #[cfg(feature = "instrument")]
+    pub(super) fn reset(&self) {
+        *self.inner.borrow_mut() = CounterSnapshot::default();
+    }
+}
@@ -0,0 +1,300 @@
+use core::borrow::Borrow;
+use core::fmt;
+use core::mem::{size_of, MaybeUninit};
+use core::ops::RangeBounds;
+
+use super::balance::{self, Keys, Links};
+use super::{Color, Compare, InvariantError, MemoryUsage, Natural};
+
+const NIL: u32 = u32::MAX;
+
+struct StaticNode<K> {
+    left: u32,
+    // 空闲节点用 right 串成空闲链表
+    right: u32,
+    parent: u32,
+    color: Color,
+    value: MaybeUninit<K>
+}
+
+impl<K> StaticNode<K> {
+    const EMPTY: Self = StaticNode { left: NIL, right: NIL, parent: NIL, color: Color::Black, value: MaybeUninit::uninit() };
+}
+
+fn to_ptr(i:u32) -> Option<u32> {
+    if i == NIL { None } else { Some(i) }
+}
+
+fn to_index(x:Option<u32>) -> u32 {
+    x.unwrap_or(NIL)
+}
+
+/// 树已满，add 把没放进去的值还回来
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityFull<K>(pub K);
+
+impl<K> fmt::Display for CapacityFull<K> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tree is full")
+    }
+}
+
+impl<K: fmt::Debug> core::error::Error for CapacityFull<K> {}
+
+/// 固定容量的红黑树，N 个节点内联在数组中，用索引链接，删除的节点进入空闲链表。
+/// 不分配内存，`new` 是 const fn，可以放在 static 或栈上
+pub struct StaticRedBlackTree<K, const N: usize, C = Natural> {
+    nodes: [StaticNode<K>; N],
+    root: u32,
+    len: u32,
+    free: u32,
+    // 从未用过的第一个节点
+    unused: u32,
+    compare: C
+}
+
+impl<K, const N: usize, C> Links for StaticRedBlackTree<K, N, C> {
+    type Ptr = u32;
+    fn root(&self) -> Option<u32> {
+        to_ptr(self.root)
+    }
+    fn set_root(&mut self, x:Option<u32>) {
+        self.root = to_index(x);
+    }
+    fn left(&self, x:&u32) -> Option<u32> {
+        to_ptr(self.nodes[*x as usize].left)
+    }
+    fn right(&self, x:&u32) -> Option<u32> {
+        to_ptr(self.nodes[*x as usize].right)
+    }
+    fn parent(&self, x:&u32) -> Option<u32> {
+        to_ptr(self.nodes[*x as usize].parent)
+    }
+    fn color(&self, x:&u32) -> Color {
+        self.nodes[*x as usize].color
+    }
+    fn set_left(&mut self, x:&u32, c:Option<u32>) {
+        self.nodes[*x as usize].left = to_index(c);
+    }
+    fn set_right(&mut self, x:&u32, c:Option<u32>) {
+        self.nodes[*x as usize].right = to_index(c);
+    }
+    fn set_parent(&mut self, x:&u32, p:Option<u32>) {
+        self.nodes[*x as usize].parent = to_index(p);
+    }
+    fn set_color(&mut self, x:&u32, color:Color) {
+        self.nodes[*x as usize].color = color;
+    }
+}
+
+impl<K, const N: usize, C> Keys for StaticRedBlackTree<K, N, C> {
+    type Key = K;
+    type Compare = C;
+    fn with_key<R>(&self, x:&u32, f:impl FnOnce(&K) -> R) -> R {
+        f(self.value(*x))
+    }
+    fn comparator(&self) -> &C {
+        &self.compare
+    }
+}
+
+impl<K, const N: usize> StaticRedBlackTree<K, N> {
+    pub const fn new() -> Self {
+        Self::with_comparator(Natural)
+    }
+}
+
+impl<K, const N: usize, C> StaticRedBlackTree<K, N, C> {
+    pub const fn with_comparator(compare:C) -> Self {
+        const { assert!(N < NIL as usize, "StaticRedBlackTree capacity must fit in u32") };
+        Self {
+            nodes: [StaticNode::EMPTY; N],
+            root: NIL,
+            len: 0,
+            free: NIL,
+            unused: 0,
+            compare
+        }
+    }
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn is_full(&self) -> bool {
+        self.len as usize == N
+    }
+    // 只对树中的节点调用
+    fn value(&self, x:u32) -> &K {
+        unsafe { self.nodes[x as usize].value.assume_init_ref() }
+    }
+    // 从空闲链表或未用过的节点中取一个
+    fn alloc(&mut self, value:K) -> Result<u32, CapacityFull<K>> {
+        let x = if self.free != NIL {
+            let x = self.free;
+            self.free = self.nodes[x as usize].right;
+            x
+        } else if (self.unused as usize) < N {
+            self.unused += 1;
+            self.unused - 1
+        } else {
+            return Err(CapacityFull(value));
+        };
+        self.nodes[x as usize].value.write(value);
+        self.len += 1;
+        Ok(x)
+    }
+    // x 已经从树中摘下
+    fn release(&mut self, x:u32) -> K {
+        let n = &mut self.nodes[x as usize];
+        n.right = self.free;
+        self.free = x;
+        self.len -= 1;
+        unsafe { n.value.assume_init_read() }
+    }
+    // 先把树置空再释放值，某个 drop panic 时剩下的值只会泄漏，不会被释放两次
+    pub fn clear(&mut self) {
+        let mut x = balance::first(self);
+        self.root = NIL;
+        self.len = 0;
+        self.free = NIL;
+        self.unused = 0;
+        while let Some(n) = x {
+            x = balance::next(self, &n);
+            unsafe { self.nodes[n as usize].value.assume_init_drop() };
+        }
+    }
+    pub fn iter(&self) -> StaticIter<'_, K, N, C> {
+        StaticIter { tree: self, cursor: balance::Cursor::all(self) }
+    }
+    pub fn first(&self) -> Option<&K> {
+        balance::first(self).map(|x| self.value(x))
+    }
+    pub fn last(&self) -> Option<&K> {
+        balance::last(self).map(|x| self.value(x))
+    }
+    // 节点数组内联在结构体中，总量就是结构体大小
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let node_bytes = size_of::<StaticNode<K>>();
+        let value_bytes = size_of::<K>();
+        let link_bytes = 3 * size_of::<u32>();
+        let color_bytes = size_of::<Color>();
+        let tree_bytes = size_of::<Self>();
+        MemoryUsage {
+            len: self.len(),
+            node_bytes,
+            value_bytes,
+            rc_header_bytes: 0,
+            refcell_flag_bytes: 0,
+            link_bytes,
+            color_bytes,
+            padding_bytes: node_bytes - link_bytes - color_bytes - value_bytes,
+            tree_bytes,
+            total_bytes: tree_bytes
+        }
+    }
+}
+
+impl<K, const N: usize, C: Compare<K>> StaticRedBlackTree<K, N, C> {
+    /// 已满且键不存在时返回 Err，树不变
+    pub fn add(&mut self, value:K) -> Result<(), CapacityFull<K>> {
+        match balance::find(self, &value) {
+            // 比较器认为相等的键直接替换
+            Ok(x) => *unsafe { self.nodes[x as usize].value.assume_init_mut() } = value,
+            Err(slot) => {
+                let x = self.alloc(value)?;
+                balance::insert_at(self, x, slot);
+            }
+        }
+        Ok(())
+    }
+    pub fn get<Q: ?Sized>(&self, value:&Q) -> Option<&K>
+    where K: Borrow<Q>, C: Compare<Q> {
+        balance::find(self, value).ok().map(|x| self.value(x))
+    }
+    pub fn contains<Q: ?Sized>(&self, value:&Q) -> bool
+    where K: Borrow<Q>, C: Compare<Q> {
+        balance::find(self, value).is_ok()
+    }
+    // 小于等于 value 的最大键
+    pub fn floor<Q: ?Sized>(&self, value:&Q) -> Option<&K>
+    where K: Borrow<Q>, C: Compare<Q> {
+        balance::floor(self, value).map(|x| self.value(x))
+    }
+    // 大于等于 value 的最小键
+    pub fn ceiling<Q: ?Sized>(&self, value:&Q) -> Option<&K>
+    where K: Borrow<Q>, C: Compare<Q> {
+        balance::ceiling(self, value).map(|x| self.value(x))
+    }
+    pub fn range<Q: ?Sized, R: RangeBounds<Q>>(&self, range:R) -> StaticIter<'_, K, N, C>
+    where K: Borrow<Q>, C: Compare<Q> {
+        StaticIter { tree: self, cursor: balance::range(self, range) }
+    }
+    pub fn take<Q: ?Sized>(&mut self, value:&Q) -> Option<K>
+    where K: Borrow<Q>, C: Compare<Q> {
+        let z = balance::take(self, value)?;
+        Some(self.release(z))
+    }
+    pub fn remove<Q: ?Sized>(&mut self, value:&Q) -> bool
+    where K: Borrow<Q>, C: Compare<Q> {
+        self.take(value).is_some()
+    }
+    pub fn del<Q: ?Sized>(&mut self, value:&Q)
+    where K: Borrow<Q>, C: Compare<Q> {
+        self.remove(value);
+    }
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        balance::validate(self, self.len())
+    }
+    pub fn check_tree(&self) -> bool {
+        self.validate().is_ok()
+    }
+}
+
+impl<K, const N: usize, C> Drop for StaticRedBlackTree<K, N, C> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// 按中序遍历键
+pub struct StaticIter<'a, K, const N: usize, C> {
+    tree: &'a StaticRedBlackTree<K, N, C>,
+    cursor: balance::Cursor<u32>
+}
+
+impl<'a, K, const N: usize, C> Iterator for StaticIter<'a, K, N, C> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let x = self.cursor.step(self.tree)?;
+        Some(self.tree.value(x))
+    }
+}
+
+impl<K, const N: usize, C: Default> Default for StaticRedBlackTree<K, N, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+// 容量相同，复制时不会满
+impl<K: Clone, const N: usize, C: Compare<K> + Clone> Clone for StaticRedBlackTree<K, N, C> {
+    fn clone(&self) -> Self {
+        let mut tree = Self::with_comparator(self.compare.clone());
+        for x in self.iter() {
+            let _ = tree.add(x.clone());
+        }
+        tree
+    }
+}
+
+impl<K: fmt::Debug, const N: usize, C> fmt::Debug for StaticRedBlackTree<K, N, C> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
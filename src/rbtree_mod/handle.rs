@@ -0,0 +1,93 @@
+use core::borrow::Borrow;
+use core::cell::Ref;
+use core::fmt;
+use alloc::rc::Rc;
+
+use super::iter::{next_node, prev_node};
+use super::{balance, Compare, NodeRef, RedBlackTree};
+
+/// 指向树中一个键的句柄。删除时重新链接节点而不在节点之间搬动值，
+/// 所以插入、删除、旋转之后句柄仍然指向同一个键，直到这个键被移除
+pub struct Handle<K>(NodeRef<K>);
+
+impl<K> Handle<K> {
+    /// 键已被 drain 或 extract_if 取出时 panic
+    pub fn value(&self) -> Ref<'_, K> {
+        Ref::map(self.0.as_ref().borrow(), |n| n.value())
+    }
+    pub fn node(&self) -> &NodeRef<K> {
+        &self.0
+    }
+}
+
+// get / iter 返回的节点也可以当作句柄
+impl<K> From<NodeRef<K>> for Handle<K> {
+    fn from(x:NodeRef<K>) -> Self {
+        Handle(x)
+    }
+}
+
+impl<K> Clone for Handle<K> {
+    fn clone(&self) -> Self {
+        Handle(self.0.clone())
+    }
+}
+
+// 按节点身份比较
+impl<K> PartialEq for Handle<K> {
+    fn eq(&self, other:&Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<K> Eq for Handle<K> {}
+
+impl<K: fmt::Debug> fmt::Debug for Handle<K> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Handle").field(&*self.value()).finish()
+    }
+}
+
+impl<K, C: Compare<K>> RedBlackTree<K, C> {
+    /// 插入并返回新键的句柄，相等的键被替换时返回原节点的句柄
+    pub fn add_handle(&mut self, value:K) -> Handle<K> {
+        Handle(self.insert_node(value))
+    }
+    pub fn handle<Q: ?Sized>(&self, value:&Q) -> Option<Handle<K>>
+    where K: Borrow<Q>, C: Compare<Q> {
+        self.get(value).map(Handle)
+    }
+    pub fn first_handle(&self) -> Option<Handle<K>> {
+        balance::first(self).map(|x| Handle(x.0))
+    }
+    pub fn last_handle(&self) -> Option<Handle<K>> {
+        balance::last(self).map(|x| Handle(x.0))
+    }
+    /// 句柄指向的键是否在这棵树中。只比较节点记下的树编号，O(1)，
+    /// 已删除的键和别的树的句柄都返回 false
+    pub fn contains_handle(&self, h:&Handle<K>) -> bool {
+        h.0.as_ref().borrow().owner == self.id
+    }
+    /// 中序的下一个键，均摊 O(1)。句柄不在这棵树中时返回 None
+    pub fn next(&self, h:&Handle<K>) -> Option<Handle<K>> {
+        if !self.contains_handle(h) {
+            return None;
+        }
+        next_node(&h.0).map(Handle)
+    }
+    /// 中序的上一个键，均摊 O(1)。句柄不在这棵树中时返回 None
+    pub fn prev(&self, h:&Handle<K>) -> Option<Handle<K>> {
+        if !self.contains_handle(h) {
+            return None;
+        }
+        prev_node(&h.0).map(Handle)
+    }
+    /// 删除句柄指向的键，不需要比较键。已经删除过或者属于别的树时返回 false
+    pub fn remove_handle(&mut self, h:&Handle<K>) -> bool {
+        if !self.contains_handle(h) {
+            return false;
+        }
+        self.delete_node(h.0.clone());
+        true
+    }
+}
@@ -0,0 +1,84 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::{Color, NodeRef, RedBlackTree, Rotation};
+
+type KeyHook<K> = Box<dyn FnMut(&K)>;
+type RotateHook<K> = Box<dyn FnMut(Rotation, &K)>;
+type RecolorHook<K> = Box<dyn FnMut(&K, Color, Color)>;
+
+pub(super) struct Hooks<K> {
+    insert: Vec<KeyHook<K>>,
+    remove: Vec<KeyHook<K>>,
+    rotate: Vec<RotateHook<K>>,
+    recolor: Vec<RecolorHook<K>>
+}
+
+impl<K> Default for Hooks<K> {
+    fn default() -> Self {
+        Self {
+            insert: vec![],
+            remove: vec![],
+            rotate: vec![],
+            recolor: vec![]
+        }
+    }
+}
+
+// 没有注册回调时 hooks 为 None，每个通知点只多一次判断
+impl<K, C> RedBlackTree<K, C> {
+    fn hooks_mut(&mut self) -> &mut Hooks<K> {
+        self.hooks.get_or_insert_with(Default::default)
+    }
+    // 新增或替换键之后触发
+    pub fn on_insert(&mut self, f:impl FnMut(&K) + 'static) {
+        self.hooks_mut().insert.push(Box::new(f));
+    }
+    // 键被移除时触发，包括 clear / retain / extract_if / drain
+    pub fn on_remove(&mut self, f:impl FnMut(&K) + 'static) {
+        self.hooks_mut().remove.push(Box::new(f));
+    }
+    // pivot 为旋转后上升的节点
+    pub fn on_rotate(&mut self, f:impl FnMut(Rotation, &K) + 'static) {
+        self.hooks_mut().rotate.push(Box::new(f));
+    }
+    pub fn on_recolor(&mut self, f:impl FnMut(&K, Color, Color) + 'static) {
+        self.hooks_mut().recolor.push(Box::new(f));
+    }
+    pub fn clear_hooks(&mut self) {
+        self.hooks = None;
+    }
+    pub(super) fn notify_insert(&mut self, x:&NodeRef<K>) {
+        if let Some(h) = self.hooks.as_mut() {
+            let x_node = x.as_ref().borrow();
+            for f in h.insert.iter_mut() {
+                f(x_node.value());
+            }
+        }
+    }
+    pub(super) fn notify_remove(&mut self, x:&NodeRef<K>) {
+        if let Some(h) = self.hooks.as_mut() {
+            let x_node = x.as_ref().borrow();
+            for f in h.remove.iter_mut() {
+                f(x_node.value());
+            }
+        }
+    }
+    pub(super) fn notify_rotate(&mut self, kind:Rotation, x:&NodeRef<K>) {
+        if let Some(h) = self.hooks.as_mut() {
+            let x_node = x.as_ref().borrow();
+            for f in h.rotate.iter_mut() {
+                f(kind, x_node.value());
+            }
+        }
+    }
+    pub(super) fn notify_recolor(&mut self, x:&NodeRef<K>, from:Color, to:Color) {
+        if let Some(h) = self.hooks.as_mut() {
+            let x_node = x.as_ref().borrow();
+            for f in h.recolor.iter_mut() {
+                f(x_node.value(), from, to);
+            }
+        }
+    }
+}
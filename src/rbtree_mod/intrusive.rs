@@ -0,0 +1,320 @@
+use core::borrow::Borrow;
+use core::cell::Cell;
+use core::fmt;
+use core::marker::{PhantomData, PhantomPinned};
+use core::ops::RangeBounds;
+use core::pin::Pin;
+use core::ptr::NonNull;
+
+use super::balance::{self, Keys, Links};
+use super::{next_tree_id, Color, Compare, InvariantError, Natural};
+
+/// 嵌入在用户类型中的链接，类似 Linux 的 `rb_node`。树只读写这些字段，不分配也不拥有元素。
+/// 含有 Link 的类型不是 Unpin，插入前要先固定 (`pin!`、`Box::pin` 或 static)
+pub struct Link {
+    left: Cell<Option<NonNull<Link>>>,
+    right: Cell<Option<NonNull<Link>>>,
+    parent: Cell<Option<NonNull<Link>>>,
+    color: Cell<Color>,
+    // 所在树的编号，0 表示没有挂在树上。同一个元素不能同时挂在两棵树上
+    owner: Cell<usize>,
+    _pin: PhantomPinned
+}
+
+impl Link {
+    pub const fn new() -> Self {
+        Link {
+            left: Cell::new(None),
+            right: Cell::new(None),
+            parent: Cell::new(None),
+            color: Cell::new(Color::Black),
+            owner: Cell::new(0),
+            _pin: PhantomPinned
+        }
+    }
+    pub fn is_linked(&self) -> bool {
+        self.owner.get() != 0
+    }
+    fn reset(&self) {
+        self.left.set(None);
+        self.right.set(None);
+        self.parent.set(None);
+        self.owner.set(0);
+    }
+}
+
+impl Default for Link {
+    fn default() -> Self {
+        Link::new()
+    }
+}
+
+impl fmt::Debug for Link {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Link").field("linked", &self.is_linked()).finish()
+    }
+}
+
+/// 说明 Link 在类型中的位置，用 `impl_linked!(Type, field)` 实现
+///
+/// # Safety
+/// `OFFSET` 必须是类型中一个 `Link` 字段的偏移
+pub unsafe trait Linked {
+    const OFFSET: usize;
+}
+
+/// `impl_linked!(Task, link)` 为 Task 实现 Linked，link 字段必须是 Link
+#[macro_export]
+macro_rules! impl_linked {
+    ($t:ty, $field:ident) => {
+        // 字段类型不是 Link 时编译失败
+        const _: fn(&$t) -> &$crate::rbtree_mod::Link = |x| &x.$field;
+        unsafe impl $crate::rbtree_mod::Linked for $t {
+            const OFFSET: usize = ::core::mem::offset_of!($t, $field);
+        }
+    };
+}
+
+type LinkPtr = NonNull<Link>;
+
+/// 侵入式红黑树：元素由调用者拥有，树只通过元素中的 Link 把它们串起来，插入删除都不分配内存。
+/// 元素以 `Pin<&'a T>` 插入，在树存在期间不能移动或释放；树释放或 clear 时把所有 Link 复位
+pub struct IntrusiveRedBlackTree<'a, T: Linked, C = Natural> {
+    root: Option<LinkPtr>,
+    len: usize,
+    // 第一次插入时分配，用来判断元素是否属于这棵树
+    id: usize,
+    compare: C,
+    _marker: PhantomData<Pin<&'a T>>
+}
+
+// Ptr 由插入的 &'a T 得到，带有整个元素的权限，可以从 Link 退回元素；
+// Link 的字段都是 Cell，通过共享引用读写
+impl<T: Linked, C> Links for IntrusiveRedBlackTree<'_, T, C> {
+    type Ptr = LinkPtr;
+    fn root(&self) -> Option<LinkPtr> {
+        self.root
+    }
+    fn set_root(&mut self, x:Option<LinkPtr>) {
+        self.root = x;
+    }
+    fn left(&self, x:&LinkPtr) -> Option<LinkPtr> {
+        unsafe { x.as_ref() }.left.get()
+    }
+    fn right(&self, x:&LinkPtr) -> Option<LinkPtr> {
+        unsafe { x.as_ref() }.right.get()
+    }
+    fn parent(&self, x:&LinkPtr) -> Option<LinkPtr> {
+        unsafe { x.as_ref() }.parent.get()
+    }
+    fn color(&self, x:&LinkPtr) -> Color {
+        unsafe { x.as_ref() }.color.get()
+    }
+    fn set_left(&mut self, x:&LinkPtr, c:Option<LinkPtr>) {
+        unsafe { x.as_ref() }.left.set(c)
+    }
+    fn set_right(&mut self, x:&LinkPtr, c:Option<LinkPtr>) {
+        unsafe { x.as_ref() }.right.set(c)
+    }
+    fn set_parent(&mut self, x:&LinkPtr, p:Option<LinkPtr>) {
+        unsafe { x.as_ref() }.parent.set(p)
+    }
+    fn set_color(&mut self, x:&LinkPtr, color:Color) {
+        unsafe { x.as_ref() }.color.set(color)
+    }
+}
+
+impl<T: Linked, C> Keys for IntrusiveRedBlackTree<'_, T, C> {
+    type Key = T;
+    type Compare = C;
+    fn with_key<R>(&self, x:&LinkPtr, f:impl FnOnce(&T) -> R) -> R {
+        f(Self::element(*x))
+    }
+    fn comparator(&self) -> &C {
+        &self.compare
+    }
+}
+
+impl<'a, T: Linked> IntrusiveRedBlackTree<'a, T> {
+    pub const fn new() -> Self {
+        Self::with_comparator(Natural)
+    }
+}
+
+impl<'a, T: Linked, C> IntrusiveRedBlackTree<'a, T, C> {
+    pub const fn with_comparator(compare:C) -> Self {
+        Self {
+            root: None,
+            len: 0,
+            id: 0,
+            compare,
+            _marker: PhantomData
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    fn link_of(x:&T) -> LinkPtr {
+        unsafe { NonNull::from(x).byte_add(T::OFFSET).cast() }
+    }
+    // x 是这棵树中的节点，元素至少活到 'a
+    fn element(x:LinkPtr) -> &'a T {
+        unsafe { x.byte_sub(T::OFFSET).cast::<T>().as_ref() }
+    }
+    // 从根往下走到叶子，复位后回到父节点，O(n) 且不递归
+    pub fn clear(&mut self) {
+        let mut x = self.root.take();
+        while let Some(n) = x {
+            if let Some(l) = self.left(&n) {
+                self.set_left(&n, None);
+                x = Some(l);
+            } else if let Some(r) = self.right(&n) {
+                self.set_right(&n, None);
+                x = Some(r);
+            } else {
+                x = self.parent(&n);
+                unsafe { n.as_ref() }.reset();
+            }
+        }
+        self.len = 0;
+    }
+    pub fn iter(&self) -> IntrusiveIter<'a, '_, T, C> {
+        IntrusiveIter { tree: self, cursor: balance::Cursor::all(self) }
+    }
+    pub fn first(&self) -> Option<&'a T> {
+        balance::first(self).map(Self::element)
+    }
+    pub fn last(&self) -> Option<&'a T> {
+        balance::last(self).map(Self::element)
+    }
+    // 只比较编号，不会去读别的树 (可能已经被 forget，节点已释放) 的链接
+    fn owns(&self, x:&T) -> bool {
+        self.id != 0 && unsafe { Self::link_of(x).as_ref() }.owner.get() == self.id
+    }
+    fn id(&mut self) -> usize {
+        if self.id == 0 {
+            self.id = next_tree_id();
+        }
+        self.id
+    }
+    /// 摘下 x，x 不在这棵树上时返回 false
+    pub fn unlink(&mut self, x:&T) -> bool {
+        if !self.owns(x) {
+            return false;
+        }
+        let z = Self::link_of(x);
+        balance::delete(self, z);
+        unsafe { z.as_ref() }.reset();
+        self.len -= 1;
+        true
+    }
+    /// 中序的下一个元素，x 必须在这棵树上
+    pub fn next(&self, x:&T) -> Option<&'a T> {
+        assert!(self.owns(x), "element is not linked into this tree");
+        balance::next(self, &Self::link_of(x)).map(Self::element)
+    }
+    /// 中序的上一个元素，x 必须在这棵树上
+    pub fn prev(&self, x:&T) -> Option<&'a T> {
+        assert!(self.owns(x), "element is not linked into this tree");
+        balance::prev(self, &Self::link_of(x)).map(Self::element)
+    }
+}
+
+impl<'a, T: Linked, C: Compare<T>> IntrusiveRedBlackTree<'a, T, C> {
+    /// 已有相等的元素时不插入，返回 false。x 已经挂在某棵树上时 panic
+    pub fn insert(&mut self, x:Pin<&'a T>) -> bool {
+        let x = x.get_ref();
+        let link = Self::link_of(x);
+        assert!(!unsafe { link.as_ref() }.is_linked(), "element is already linked into a tree");
+        match balance::find(self, x) {
+            Ok(_) => false,
+            Err(slot) => {
+                let id = self.id();
+                unsafe { link.as_ref() }.owner.set(id);
+                balance::insert_at(self, link, slot);
+                self.len += 1;
+                true
+            }
+        }
+    }
+    pub fn get<Q: ?Sized>(&self, value:&Q) -> Option<&'a T>
+    where T: Borrow<Q>, C: Compare<Q> {
+        balance::find(self, value).ok().map(Self::element)
+    }
+    pub fn contains<Q: ?Sized>(&self, value:&Q) -> bool
+    where T: Borrow<Q>, C: Compare<Q> {
+        balance::find(self, value).is_ok()
+    }
+    // 小于等于 value 的最大元素
+    pub fn floor<Q: ?Sized>(&self, value:&Q) -> Option<&'a T>
+    where T: Borrow<Q>, C: Compare<Q> {
+        balance::floor(self, value).map(Self::element)
+    }
+    // 大于等于 value 的最小元素
+    pub fn ceiling<Q: ?Sized>(&self, value:&Q) -> Option<&'a T>
+    where T: Borrow<Q>, C: Compare<Q> {
+        balance::ceiling(self, value).map(Self::element)
+    }
+    pub fn range<Q: ?Sized, R: RangeBounds<Q>>(&self, range:R) -> IntrusiveIter<'a, '_, T, C>
+    where T: Borrow<Q>, C: Compare<Q> {
+        IntrusiveIter { tree: self, cursor: balance::range(self, range) }
+    }
+    /// 按键摘下元素并还给调用者
+    pub fn remove<Q: ?Sized>(&mut self, value:&Q) -> Option<Pin<&'a T>>
+    where T: Borrow<Q>, C: Compare<Q> {
+        let x = Self::element(balance::find(self, value).ok()?);
+        self.unlink(x);
+        // 插入时就是固定的
+        Some(unsafe { Pin::new_unchecked(x) })
+    }
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        balance::validate(self, self.len)
+    }
+    pub fn check_tree(&self) -> bool {
+        self.validate().is_ok()
+    }
+}
+
+impl<T: Linked, C> Drop for IntrusiveRedBlackTree<'_, T, C> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// 按中序遍历元素
+pub struct IntrusiveIter<'a, 't, T: Linked, C> {
+    tree: &'t IntrusiveRedBlackTree<'a, T, C>,
+    cursor: balance::Cursor<LinkPtr>
+}
+
+impl<'a, T: Linked, C> Iterator for IntrusiveIter<'a, '_, T, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let x = self.cursor.step(self.tree)?;
+        Some(IntrusiveRedBlackTree::<T, C>::element(x))
+    }
+}
+
+impl<T: Linked, C: Default> Default for IntrusiveRedBlackTree<'_, T, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+impl<'a, T: Linked, C: Compare<T>> Extend<Pin<&'a T>> for IntrusiveRedBlackTree<'a, T, C> {
+    fn extend<I: IntoIterator<Item = Pin<&'a T>>>(&mut self, iter:I) {
+        for x in iter {
+            self.insert(x);
+        }
+    }
+}
+
+impl<T: Linked + fmt::Debug, C> fmt::Debug for IntrusiveRedBlackTree<'_, T, C> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
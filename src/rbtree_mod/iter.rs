@@ -0,0 +1,82 @@
+use core::marker::PhantomData;
+use alloc::rc::Rc;
+
+use super::NodeRef;
+
+// 中序后继
+pub(super) fn next_node<K>(x:&NodeRef<K>) -> Option<NodeRef<K>> {
+    let right = x.as_ref().borrow().right.clone();
+    if let Some(mut n) = right {
+        loop {
+            let left = n.as_ref().borrow().left.clone();
+            match left {
+                Some(l) => n = l,
+                None => return Some(n)
+            }
+        }
+    }
+    let mut x = x.clone();
+    loop {
+        let p = x.as_ref().borrow().parent_node()?;
+        let from_left = super::is_same(&p.as_ref().borrow().left, &x);
+        if from_left {
+            return Some(p);
+        }
+        x = p;
+    }
+}
+
+// 中序前驱
+pub(super) fn prev_node<K>(x:&NodeRef<K>) -> Option<NodeRef<K>> {
+    let left = x.as_ref().borrow().left.clone();
+    if let Some(mut n) = left {
+        loop {
+            let right = n.as_ref().borrow().right.clone();
+            match right {
+                Some(r) => n = r,
+                None => return Some(n)
+            }
+        }
+    }
+    let mut x = x.clone();
+    loop {
+        let p = x.as_ref().borrow().parent_node()?;
+        let from_right = super::is_same(&p.as_ref().borrow().right, &x);
+        if from_right {
+            return Some(p);
+        }
+        x = p;
+    }
+}
+
+/// 按中序遍历节点，`end` 为第一个不再返回的节点
+pub struct Iter<'a, K> {
+    next: Option<NodeRef<K>>,
+    end: Option<NodeRef<K>>,
+    _marker: PhantomData<&'a K>
+}
+
+impl<'a, K> Iter<'a, K> {
+    pub(super) fn new(next:Option<NodeRef<K>>, end:Option<NodeRef<K>>) -> Self {
+        Self {
+            next,
+            end,
+            _marker: PhantomData
+        }
+    }
+}
+
+impl<'a, K> Iterator for Iter<'a, K> {
+    type Item = NodeRef<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let x = self.next.take()?;
+        if let Some(end) = &self.end {
+            if Rc::ptr_eq(&x, end) {
+                return None;
+            }
+        }
+        self.next = next_node(&x);
+        Some(x)
+    }
+}
@@ -0,0 +1,78 @@
+use core::alloc::Layout;
+use core::cell::RefCell;
+use core::fmt;
+use core::mem::size_of;
+use alloc::rc::Weak;
+
+use super::{Color, Node, NodeRef, RedBlackTree};
+
+/// 内存占用估算，单位字节。只统计树自身的分配，不包括 K 内部再分配的堆内存
+/// (例如 String 的字符数据)，也不包括分配器的对齐和元数据开销
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryUsage {
+    pub len: usize,
+    // 每个节点一次分配：Rc 的 strong/weak 计数 + RefCell<Node<K>>
+    pub node_bytes: usize,
+    // 其中 K 本身占用的部分
+    pub value_bytes: usize,
+    // 以下为 node_bytes 中各部分的大小
+    pub rc_header_bytes: usize,
+    pub refcell_flag_bytes: usize,
+    pub link_bytes: usize,
+    pub color_bytes: usize,
+    pub padding_bytes: usize,
+    // RedBlackTree 结构体本身
+    pub tree_bytes: usize,
+    pub total_bytes: usize
+}
+
+impl MemoryUsage {
+    pub fn overhead_per_node(&self) -> usize {
+        self.node_bytes - self.value_bytes
+    }
+}
+
+impl fmt::Display for MemoryUsage {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "len:            {}", self.len)?;
+        writeln!(f, "bytes per node: {} (value {}, overhead {})", self.node_bytes, self.value_bytes, self.overhead_per_node())?;
+        writeln!(f, "  Rc header:    {}", self.rc_header_bytes)?;
+        writeln!(f, "  RefCell flag: {}", self.refcell_flag_bytes)?;
+        writeln!(f, "  links:        {}", self.link_bytes)?;
+        writeln!(f, "  color:        {}", self.color_bytes)?;
+        writeln!(f, "  padding:      {}", self.padding_bytes)?;
+        writeln!(f, "tree struct:    {}", self.tree_bytes)?;
+        write!(f, "total:          {}", self.total_bytes)
+    }
+}
+
+impl<K, C> RedBlackTree<K, C> {
+    pub fn memory_usage(&self) -> MemoryUsage {
+        // Rc::new 分配的 RcBox 布局：两个 usize 计数，后面接 RefCell<Node<K>>
+        let (rc_box, _) = Layout::new::<[usize; 2]>()
+            .extend(Layout::new::<RefCell<Node<K>>>())
+            .unwrap();
+        let node_bytes = rc_box.pad_to_align().size();
+        let value_bytes = size_of::<K>();
+        let rc_header_bytes = 2 * size_of::<usize>();
+        // RefCell 的借用计数是一个 isize
+        let refcell_flag_bytes = size_of::<isize>();
+        // left、right、parent 和所在树的编号，和 Link 的字段一样
+        let link_bytes = 2 * size_of::<Option<NodeRef<K>>>() + size_of::<Option<Weak<RefCell<Node<K>>>>>() + size_of::<usize>();
+        let color_bytes = size_of::<Color>();
+        let padding_bytes = node_bytes - rc_header_bytes - refcell_flag_bytes - link_bytes - color_bytes - value_bytes;
+        let tree_bytes = size_of::<Self>();
+        MemoryUsage {
+            len: self.len,
+            node_bytes,
+            value_bytes,
+            rc_header_bytes,
+            refcell_flag_bytes,
+            link_bytes,
+            color_bytes,
+            padding_bytes,
+            tree_bytes,
+            total_bytes: tree_bytes + self.len * node_bytes
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use core::alloc::Layout;
+use core::ptr::NonNull;
+
+/// RawRedBlackTree 的节点分配器，树拥有它，每个节点调用一次 allocate，删除或 clear 时 deallocate。
+/// 所有节点的 Layout 相同，所以 slab、对象池只需按第一次请求的大小切块。
+/// 想让多棵树共用一个分配器 (比如 bump arena) 时，为 `&Arena` 实现这个 trait，内部用 Cell/RefCell。
+///
+/// 只有 RawRedBlackTree 接受分配器。RedBlackTree 的节点是 `Rc<RefCell<Node>>`，而 `Rc::new_in`
+/// (allocator_api) 在 stable 上还不能用，节点总是来自全局分配器；CompactRedBlackTree 的节点连续放在一个 Vec 中，
+/// 不逐个分配；StaticRedBlackTree 和 IntrusiveRedBlackTree 本身不分配内存。
+///
+/// # Safety
+/// allocate 返回的内存必须满足 layout 的大小和对齐，并且在 deallocate 之前一直有效、不与其他分配重叠
+pub unsafe trait NodeAlloc {
+    /// 失败返回 None，树会调用 `handle_alloc_error`
+    fn allocate(&mut self, layout:Layout) -> Option<NonNull<u8>>;
+    /// # Safety
+    /// ptr 必须由同一个分配器以相同的 layout 分配，且只释放一次
+    unsafe fn deallocate(&mut self, ptr:NonNull<u8>, layout:Layout);
+}
+
+/// 默认分配器，转发给全局分配器
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Global;
+
+// 节点至少包含三个链接，layout 不会是 0 字节
+unsafe impl NodeAlloc for Global {
+    fn allocate(&mut self, layout:Layout) -> Option<NonNull<u8>> {
+        NonNull::new(unsafe { alloc::alloc::alloc(layout) })
+    }
+    unsafe fn deallocate(&mut self, ptr:NonNull<u8>, layout:Layout) {
+        alloc::alloc::dealloc(ptr.as_ptr(), layout)
+    }
+}
+
+unsafe impl<A: NodeAlloc + ?Sized> NodeAlloc for &mut A {
+    fn allocate(&mut self, layout:Layout) -> Option<NonNull<u8>> {
+        (**self).allocate(layout)
+    }
+    unsafe fn deallocate(&mut self, ptr:NonNull<u8>, layout:Layout) {
+        (**self).deallocate(ptr, layout)
+    }
+}
@@ -0,0 +1,87 @@
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, Sub, SubAssign};
+use alloc::vec::Vec;
+
+use super::{Compare, RedBlackTree};
+
+impl<K: Clone, C: Compare<K> + Clone> RedBlackTree<K, C> {
+    // self 中满足 f 的键组成的新树，比较器沿用 self 的
+    fn filter_clone(&self, f:impl Fn(&K) -> bool) -> Self {
+        let mut tree = Self::with_comparator(self.compare.clone());
+        for x in self.iter() {
+            let x_node = x.as_ref().borrow();
+            if f(x_node.value()) {
+                tree.add(x_node.value().clone());
+            }
+        }
+        tree
+    }
+}
+
+impl<K: Clone, C: Compare<K> + Clone> BitOr<&RedBlackTree<K, C>> for &RedBlackTree<K, C> {
+    type Output = RedBlackTree<K, C>;
+
+    fn bitor(self, rhs:&RedBlackTree<K, C>) -> Self::Output {
+        let mut tree = self.clone();
+        tree |= rhs;
+        tree
+    }
+}
+
+impl<K: Clone, C: Compare<K> + Clone> BitAnd<&RedBlackTree<K, C>> for &RedBlackTree<K, C> {
+    type Output = RedBlackTree<K, C>;
+
+    fn bitand(self, rhs:&RedBlackTree<K, C>) -> Self::Output {
+        self.filter_clone(|k| rhs.contains(k))
+    }
+}
+
+impl<K: Clone, C: Compare<K> + Clone> Sub<&RedBlackTree<K, C>> for &RedBlackTree<K, C> {
+    type Output = RedBlackTree<K, C>;
+
+    fn sub(self, rhs:&RedBlackTree<K, C>) -> Self::Output {
+        self.filter_clone(|k| !rhs.contains(k))
+    }
+}
+
+impl<K: Clone, C: Compare<K> + Clone> BitXor<&RedBlackTree<K, C>> for &RedBlackTree<K, C> {
+    type Output = RedBlackTree<K, C>;
+
+    fn bitxor(self, rhs:&RedBlackTree<K, C>) -> Self::Output {
+        let mut tree = self - rhs;
+        for x in rhs.iter() {
+            let x_node = x.as_ref().borrow();
+            if !self.contains(x_node.value()) {
+                tree.add(x_node.value().clone());
+            }
+        }
+        tree
+    }
+}
+
+impl<K: Clone, C: Compare<K>> BitOrAssign<&RedBlackTree<K, C>> for RedBlackTree<K, C> {
+    fn bitor_assign(&mut self, rhs:&RedBlackTree<K, C>) {
+        for x in rhs.iter() {
+            self.add(x.as_ref().borrow().value().clone());
+        }
+    }
+}
+
+impl<K: Clone, C: Compare<K>> BitAndAssign<&RedBlackTree<K, C>> for RedBlackTree<K, C> {
+    fn bitand_assign(&mut self, rhs:&RedBlackTree<K, C>) {
+        let removed: Vec<K> = self.iter()
+            .map(|x| x.as_ref().borrow().value().clone())
+            .filter(|k| !rhs.contains(k))
+            .collect();
+        for k in removed {
+            self.remove(&k);
+        }
+    }
+}
+
+impl<K, C: Compare<K>> SubAssign<&RedBlackTree<K, C>> for RedBlackTree<K, C> {
+    fn sub_assign(&mut self, rhs:&RedBlackTree<K, C>) {
+        for x in rhs.iter() {
+            self.remove(x.as_ref().borrow().value());
+        }
+    }
+}
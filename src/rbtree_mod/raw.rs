@@ -0,0 +1,298 @@
+use core::borrow::Borrow;
+use core::fmt;
+use core::alloc::Layout;
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::ops::RangeBounds;
+use core::ptr::{self, NonNull};
+use alloc::alloc::handle_alloc_error;
+use alloc::boxed::Box;
+
+use super::balance::{self, Keys, Links};
+use super::{Color, Compare, Global, InvariantError, MemoryUsage, Natural, NodeAlloc};
+
+pub(super) struct RawNode<K> {
+    left: Option<NonNull<RawNode<K>>>,
+    right: Option<NonNull<RawNode<K>>>,
+    parent: Option<NonNull<RawNode<K>>>,
+    color: Color,
+    value: K
+}
+
+type RawPtr<K> = NonNull<RawNode<K>>;
+
+/// 裸指针实现的红黑树：每个节点从分配器 A 单独分配，链接是 NonNull，没有 Rc 计数和 RefCell 借用检查。
+/// 节点只归树所有，从不交给外部，所以只返回 &K。`cargo +nightly miri test --test raw` 检查未定义行为和泄漏
+pub struct RawRedBlackTree<K, C = Natural, A: NodeAlloc = Global> {
+    root: Option<RawPtr<K>>,
+    len: usize,
+    compare: C,
+    alloc: A,
+    // 树拥有所有节点
+    _marker: PhantomData<Box<RawNode<K>>>
+}
+
+// 和 Box<K> 一样，节点不会被共享
+unsafe impl<K: Send, C: Send, A: NodeAlloc + Send> Send for RawRedBlackTree<K, C, A> {}
+unsafe impl<K: Sync, C: Sync, A: NodeAlloc + Sync> Sync for RawRedBlackTree<K, C, A> {}
+
+// 所有 Ptr 都来自这棵树中还活着的节点，&self / &mut self 保证读写不冲突
+impl<K, C, A: NodeAlloc> Links for RawRedBlackTree<K, C, A> {
+    type Ptr = RawPtr<K>;
+    fn root(&self) -> Option<RawPtr<K>> {
+        self.root
+    }
+    fn set_root(&mut self, x:Option<RawPtr<K>>) {
+        self.root = x;
+    }
+    fn left(&self, x:&RawPtr<K>) -> Option<RawPtr<K>> {
+        unsafe { (*x.as_ptr()).left }
+    }
+    fn right(&self, x:&RawPtr<K>) -> Option<RawPtr<K>> {
+        unsafe { (*x.as_ptr()).right }
+    }
+    fn parent(&self, x:&RawPtr<K>) -> Option<RawPtr<K>> {
+        unsafe { (*x.as_ptr()).parent }
+    }
+    fn color(&self, x:&RawPtr<K>) -> Color {
+        unsafe { (*x.as_ptr()).color }
+    }
+    fn set_left(&mut self, x:&RawPtr<K>, c:Option<RawPtr<K>>) {
+        unsafe { (*x.as_ptr()).left = c }
+    }
+    fn set_right(&mut self, x:&RawPtr<K>, c:Option<RawPtr<K>>) {
+        unsafe { (*x.as_ptr()).right = c }
+    }
+    fn set_parent(&mut self, x:&RawPtr<K>, p:Option<RawPtr<K>>) {
+        unsafe { (*x.as_ptr()).parent = p }
+    }
+    fn set_color(&mut self, x:&RawPtr<K>, color:Color) {
+        unsafe { (*x.as_ptr()).color = color }
+    }
+}
+
+impl<K, C, A: NodeAlloc> Keys for RawRedBlackTree<K, C, A> {
+    type Key = K;
+    type Compare = C;
+    fn with_key<R>(&self, x:&RawPtr<K>, f:impl FnOnce(&K) -> R) -> R {
+        f(self.value(*x))
+    }
+    fn comparator(&self) -> &C {
+        &self.compare
+    }
+}
+
+impl<K> RawRedBlackTree<K> {
+    pub fn new() -> Self {
+        Self::with_comparator(Natural)
+    }
+}
+
+impl<K, C> RawRedBlackTree<K, C> {
+    pub fn with_comparator(compare:C) -> Self {
+        Self::with_comparator_in(compare, Global)
+    }
+}
+
+impl<K, A: NodeAlloc> RawRedBlackTree<K, Natural, A> {
+    pub fn new_in(alloc:A) -> Self {
+        Self::with_comparator_in(Natural, alloc)
+    }
+}
+
+impl<K, C, A: NodeAlloc> RawRedBlackTree<K, C, A> {
+    pub fn with_comparator_in(compare:C, alloc:A) -> Self {
+        Self {
+            root: None,
+            len: 0,
+            compare,
+            alloc,
+            _marker: PhantomData
+        }
+    }
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+    fn alloc_node(&mut self, value:K) -> RawPtr<K> {
+        let layout = Layout::new::<RawNode<K>>();
+        let Some(p) = self.alloc.allocate(layout) else {
+            handle_alloc_error(layout);
+        };
+        let x = p.cast::<RawNode<K>>();
+        unsafe { x.as_ptr().write(RawNode { left: None, right: None, parent: None, color: Color::Red, value }) };
+        x
+    }
+    // x 已经不在树中，取出值后把内存还给分配器
+    fn free_node(&mut self, x:RawPtr<K>) -> K {
+        let value = unsafe { ptr::read(&(*x.as_ptr()).value) };
+        unsafe { self.alloc.deallocate(x.cast(), Layout::new::<RawNode<K>>()) };
+        value
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    // 从根往下走到叶子，释放后回到父节点，O(n) 且不递归
+    pub fn clear(&mut self) {
+        let mut x = self.root.take();
+        while let Some(n) = x {
+            if let Some(l) = self.left(&n) {
+                self.set_left(&n, None);
+                x = Some(l);
+            } else if let Some(r) = self.right(&n) {
+                self.set_right(&n, None);
+                x = Some(r);
+            } else {
+                x = self.parent(&n);
+                drop(self.free_node(n));
+            }
+        }
+        self.len = 0;
+    }
+    fn value(&self, x:RawPtr<K>) -> &K {
+        unsafe { &(*x.as_ptr()).value }
+    }
+    pub fn iter(&self) -> RawIter<'_, K, C, A> {
+        RawIter { tree: self, cursor: balance::Cursor::all(self) }
+    }
+    pub fn first(&self) -> Option<&K> {
+        balance::first(self).map(|x| self.value(x))
+    }
+    pub fn last(&self) -> Option<&K> {
+        balance::last(self).map(|x| self.value(x))
+    }
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let node_bytes = size_of::<RawNode<K>>();
+        let value_bytes = size_of::<K>();
+        let link_bytes = 3 * size_of::<Option<RawPtr<K>>>();
+        let color_bytes = size_of::<Color>();
+        let tree_bytes = size_of::<Self>();
+        MemoryUsage {
+            len: self.len,
+            node_bytes,
+            value_bytes,
+            rc_header_bytes: 0,
+            refcell_flag_bytes: 0,
+            link_bytes,
+            color_bytes,
+            padding_bytes: node_bytes - link_bytes - color_bytes - value_bytes,
+            tree_bytes,
+            total_bytes: tree_bytes + self.len * node_bytes
+        }
+    }
+}
+
+impl<K, C: Compare<K>, A: NodeAlloc> RawRedBlackTree<K, C, A> {
+    pub fn add(&mut self, value:K) {
+        match balance::find(self, &value) {
+            // 比较器认为相等的键直接替换
+            Ok(x) => unsafe { (*x.as_ptr()).value = value },
+            Err(slot) => {
+                let x = self.alloc_node(value);
+                balance::insert_at(self, x, slot);
+                self.len += 1;
+            }
+        }
+    }
+    pub fn get<Q: ?Sized>(&self, value:&Q) -> Option<&K>
+    where K: Borrow<Q>, C: Compare<Q> {
+        balance::find(self, value).ok().map(|x| self.value(x))
+    }
+    pub fn contains<Q: ?Sized>(&self, value:&Q) -> bool
+    where K: Borrow<Q>, C: Compare<Q> {
+        balance::find(self, value).is_ok()
+    }
+    // 小于等于 value 的最大键
+    pub fn floor<Q: ?Sized>(&self, value:&Q) -> Option<&K>
+    where K: Borrow<Q>, C: Compare<Q> {
+        balance::floor(self, value).map(|x| self.value(x))
+    }
+    // 大于等于 value 的最小键
+    pub fn ceiling<Q: ?Sized>(&self, value:&Q) -> Option<&K>
+    where K: Borrow<Q>, C: Compare<Q> {
+        balance::ceiling(self, value).map(|x| self.value(x))
+    }
+    pub fn range<Q: ?Sized, R: RangeBounds<Q>>(&self, range:R) -> RawIter<'_, K, C, A>
+    where K: Borrow<Q>, C: Compare<Q> {
+        RawIter { tree: self, cursor: balance::range(self, range) }
+    }
+    pub fn take<Q: ?Sized>(&mut self, value:&Q) -> Option<K>
+    where K: Borrow<Q>, C: Compare<Q> {
+        let z = balance::take(self, value)?;
+        self.len -= 1;
+        Some(self.free_node(z))
+    }
+    pub fn remove<Q: ?Sized>(&mut self, value:&Q) -> bool
+    where K: Borrow<Q>, C: Compare<Q> {
+        self.take(value).is_some()
+    }
+    pub fn del<Q: ?Sized>(&mut self, value:&Q)
+    where K: Borrow<Q>, C: Compare<Q> {
+        self.remove(value);
+    }
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        balance::validate(self, self.len)
+    }
+    pub fn check_tree(&self) -> bool {
+        self.validate().is_ok()
+    }
+}
+
+impl<K, C, A: NodeAlloc> Drop for RawRedBlackTree<K, C, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// 按中序遍历键
+pub struct RawIter<'a, K, C, A: NodeAlloc = Global> {
+    tree: &'a RawRedBlackTree<K, C, A>,
+    cursor: balance::Cursor<RawPtr<K>>
+}
+
+impl<'a, K, C, A: NodeAlloc> Iterator for RawIter<'a, K, C, A> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let x = self.cursor.step(self.tree)?;
+        Some(self.tree.value(x))
+    }
+}
+
+impl<K, C: Default, A: NodeAlloc + Default> Default for RawRedBlackTree<K, C, A> {
+    fn default() -> Self {
+        Self::with_comparator_in(C::default(), A::default())
+    }
+}
+
+impl<K: Clone, C: Compare<K> + Clone, A: NodeAlloc + Clone> Clone for RawRedBlackTree<K, C, A> {
+    fn clone(&self) -> Self {
+        let mut tree = Self::with_comparator_in(self.compare.clone(), self.alloc.clone());
+        tree.extend(self.iter().cloned());
+        tree
+    }
+}
+
+impl<K, C: Compare<K> + Default, A: NodeAlloc + Default> FromIterator<K> for RawRedBlackTree<K, C, A> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter:I) -> Self {
+        let mut tree = Self::default();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K, C: Compare<K>, A: NodeAlloc> Extend<K> for RawRedBlackTree<K, C, A> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter:I) {
+        for value in iter {
+            self.add(value);
+        }
+    }
+}
+
+impl<K: fmt::Debug, C, A: NodeAlloc> fmt::Debug for RawRedBlackTree<K, C, A> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
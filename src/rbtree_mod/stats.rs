@@ -0,0 +1,75 @@
+use core::fmt;
+use alloc::vec::Vec;
+
+use super::{Color, RedBlackTree};
+
+/// 树的形状统计，depth 从根节点的 0 开始，叶子指没有子节点的节点
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TreeStats {
+    pub len: usize,
+    pub height: usize,
+    pub black_height: usize,
+    pub red_count: usize,
+    pub black_count: usize,
+    pub leaf_count: usize,
+    pub avg_leaf_depth: f64,
+    pub max_leaf_depth: usize,
+    // depth_histogram[d] 为深度 d 的节点数
+    pub depth_histogram: Vec<usize>
+}
+
+impl fmt::Display for TreeStats {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "len:            {}", self.len)?;
+        writeln!(f, "height:         {}", self.height)?;
+        writeln!(f, "black height:   {}", self.black_height)?;
+        writeln!(f, "red / black:    {} / {}", self.red_count, self.black_count)?;
+        writeln!(f, "leaves:         {}", self.leaf_count)?;
+        writeln!(f, "leaf depth:     avg {:.2}, max {}", self.avg_leaf_depth, self.max_leaf_depth)?;
+        write!(f, "depth histogram:")?;
+        for (depth, count) in self.depth_histogram.iter().enumerate() {
+            write!(f, "\n  {:>3}: {}", depth, count)?;
+        }
+        Ok(())
+    }
+}
+
+impl<K, C> RedBlackTree<K, C> {
+    pub fn stats(&self) -> TreeStats {
+        let mut stats = TreeStats {
+            len: self.len,
+            ..TreeStats::default()
+        };
+        let mut leaf_depth_sum = 0;
+        for v in self.iter_level_order() {
+            match v.color {
+                Color::Red => stats.red_count += 1,
+                Color::Black => stats.black_count += 1
+            }
+            if stats.depth_histogram.len() <= v.depth {
+                stats.depth_histogram.push(0);
+            }
+            stats.depth_histogram[v.depth] += 1;
+            let x_node = v.node().as_ref().borrow();
+            if x_node.left.is_none() && x_node.right.is_none() {
+                stats.leaf_count += 1;
+                leaf_depth_sum += v.depth;
+                stats.max_leaf_depth = stats.max_leaf_depth.max(v.depth);
+            }
+        }
+        stats.height = stats.depth_histogram.len();
+        if stats.leaf_count > 0 {
+            stats.avg_leaf_depth = leaf_depth_sum as f64 / stats.leaf_count as f64;
+        }
+        // 平衡的树每条路径黑节点数相同，沿最左路径计算
+        let mut x = self.root.clone();
+        while let Some(n) = x {
+            let n_node = n.as_ref().borrow();
+            if n_node.color == Color::Black {
+                stats.black_height += 1;
+            }
+            x = n_node.left.clone();
+        }
+        stats
+    }
+}
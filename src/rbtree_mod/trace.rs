@@ -0,0 +1,259 @@
+use core::fmt::{self, Debug, Write};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::{Color, NodeRef, RedBlackTree};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Left,
+    Right
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceEvent {
+    Rotate { kind: Rotation, pivot: String },
+    Recolor { key: String, from: Color, to: Color },
+    Swap { a: String, b: String },
+    Remove { key: String }
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceEvent::Rotate { kind: Rotation::Left, pivot } => write!(f, "left_rotate {}", pivot),
+            TraceEvent::Rotate { kind: Rotation::Right, pivot } => write!(f, "right_rotate {}", pivot),
+            TraceEvent::Recolor { key, from, to } => write!(f, "recolor {} {:?} -> {:?}", key, from, to),
+            TraceEvent::Swap { a, b } => write!(f, "swap {} <-> {}", a, b),
+            TraceEvent::Remove { key } => write!(f, "remove {}", key)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameNode {
+    pub key: String,
+    pub color: Color,
+    pub left: Option<usize>,
+    pub right: Option<usize>
+}
+
+/// 某一时刻整棵树的快照，`nodes[0]` 为根节点
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Frame {
+    pub nodes: Vec<FrameNode>
+}
+
+impl Frame {
+    fn capture<K>(root:&Option<NodeRef<K>>, fmt:fn(&K) -> String) -> Self {
+        let mut frame = Frame::default();
+        // (节点, 父节点下标及是否为右子)
+        let mut stack = vec![];
+        if let Some(root) = root {
+            stack.push((root.clone(), None::<(usize, bool)>));
+        }
+        while let Some((x, parent)) = stack.pop() {
+            let i = frame.nodes.len();
+            let x_node = x.as_ref().borrow();
+            frame.nodes.push(FrameNode {
+                key: fmt(x_node.value()),
+                color: x_node.color,
+                left: None,
+                right: None
+            });
+            match parent {
+                Some((p, false)) => frame.nodes[p].left = Some(i),
+                Some((p, true)) => frame.nodes[p].right = Some(i),
+                None => {}
+            }
+            if let Some(r) = x_node.right.clone() {
+                stack.push((r, Some((i, true))));
+            }
+            if let Some(l) = x_node.left.clone() {
+                stack.push((l, Some((i, false))));
+            }
+        }
+        frame
+    }
+    // 横向打印，右子树在上
+    pub fn to_ascii(&self) -> String {
+        let mut out = String::new();
+        if self.nodes.is_empty() {
+            out.push_str("(empty)\n");
+            return out;
+        }
+        let mut stack = vec![(0, 0, false)];
+        while let Some((i, depth, expanded)) = stack.pop() {
+            let n = &self.nodes[i];
+            if expanded {
+                let color = match n.color {
+                    Color::Red => 'R',
+                    Color::Black => 'B'
+                };
+                let _ = writeln!(out, "{}{}[{}]", "    ".repeat(depth), n.key, color);
+                continue;
+            }
+            if let Some(l) = n.left {
+                stack.push((l, depth + 1, false));
+            }
+            stack.push((i, depth, true));
+            if let Some(r) = n.right {
+                stack.push((r, depth + 1, false));
+            }
+        }
+        out
+    }
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph rbtree {\n    node [style=filled, fontcolor=white];\n");
+        let mut nil = 0;
+        for (i, n) in self.nodes.iter().enumerate() {
+            let fill = match n.color {
+                Color::Red => "red",
+                Color::Black => "black"
+            };
+            let _ = writeln!(out, "    n{} [label={:?}, fillcolor={}];", i, n.key, fill);
+            for child in [n.left, n.right] {
+                match child {
+                    Some(c) => {
+                        let _ = writeln!(out, "    n{} -> n{};", i, c);
+                    },
+                    None => {
+                        let _ = writeln!(out, "    nil{} [shape=point];\n    n{} -> nil{};", nil, i, nil);
+                        nil += 1;
+                    }
+                }
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// 一个步骤：触发的规则、期间的旋转/变色，以及步骤结束时的树
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    pub label: String,
+    pub events: Vec<TraceEvent>,
+    pub frame: Frame
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Trace {
+    pub steps: Vec<TraceStep>
+}
+
+impl Trace {
+    pub fn render_ascii(&self) -> String {
+        let mut out = String::new();
+        for (i, step) in self.steps.iter().enumerate() {
+            let _ = writeln!(out, "== {} {}", i, step.label);
+            for e in &step.events {
+                let _ = writeln!(out, "   {}", e);
+            }
+            out.push_str(&step.frame.to_ascii());
+        }
+        out
+    }
+    pub fn render_dot(&self) -> Vec<String> {
+        self.steps.iter().map(|step| step.frame.to_dot()).collect()
+    }
+}
+
+pub(super) struct Tracer<K> {
+    fmt: fn(&K) -> String,
+    trace: Trace,
+    // 最后一步的 frame 还没有拍下
+    open: bool
+}
+
+fn debug_fmt<K: Debug>(k:&K) -> String {
+    format!("{:?}", k)
+}
+
+impl<K: Debug, C> RedBlackTree<K, C> {
+    // 当前树的快照，可用 to_ascii / to_dot 输出
+    pub fn frame(&self) -> Frame {
+        Frame::capture(&self.root, debug_fmt::<K>)
+    }
+    // 开始记录 add / del 的每个步骤，已有的记录会被清空
+    pub fn start_trace(&mut self) {
+        self.tracer = Some(Box::new(Tracer {
+            fmt: debug_fmt::<K>,
+            trace: Trace::default(),
+            open: false
+        }));
+    }
+}
+
+impl<K, C> RedBlackTree<K, C> {
+    pub fn is_tracing(&self) -> bool {
+        self.tracer.is_some()
+    }
+    // 停止记录并取出结果
+    pub fn take_trace(&mut self) -> Option<Trace> {
+        self.trace_close();
+        self.tracer.take().map(|t| t.trace)
+    }
+    fn trace_close(&mut self) {
+        if let Some(t) = self.tracer.as_mut() {
+            if t.open {
+                let frame = Frame::capture(&self.root, t.fmt);
+                if let Some(step) = t.trace.steps.last_mut() {
+                    step.frame = frame;
+                }
+                t.open = false;
+            }
+        }
+    }
+    // 开始一个新步骤，上一步骤在此时定格
+    pub(super) fn trace_case(&mut self, label:impl FnOnce() -> String) {
+        if self.tracer.is_none() {
+            return;
+        }
+        self.trace_close();
+        if let Some(t) = self.tracer.as_mut() {
+            t.trace.steps.push(TraceStep {
+                label: label(),
+                events: vec![],
+                frame: Frame::default()
+            });
+            t.open = true;
+        }
+    }
+    // 操作开始，该步骤在第一个规则触发前定格
+    pub(super) fn trace_begin(&mut self, op:&str, x:&K) {
+        if let Some(t) = self.tracer.as_ref() {
+            let label = format!("{} {}", op, (t.fmt)(x));
+            self.trace_case(|| label);
+        }
+    }
+    pub(super) fn trace_end(&mut self) {
+        self.trace_close();
+    }
+    fn trace_event(&mut self, f:impl FnOnce(fn(&K) -> String) -> TraceEvent) {
+        if let Some(t) = self.tracer.as_mut() {
+            let e = f(t.fmt);
+            if let Some(step) = t.trace.steps.last_mut() {
+                step.events.push(e);
+            }
+        }
+    }
+    pub(super) fn trace_rotate(&mut self, kind:Rotation, x:&NodeRef<K>) {
+        self.trace_event(|fmt| TraceEvent::Rotate { kind, pivot: fmt(x.as_ref().borrow().value()) });
+    }
+    pub(super) fn trace_recolor(&mut self, x:&NodeRef<K>, from:Color, to:Color) {
+        self.trace_event(|fmt| TraceEvent::Recolor { key: fmt(x.as_ref().borrow().value()), from, to });
+    }
+    pub(super) fn trace_swap(&mut self, a:&NodeRef<K>, b:&NodeRef<K>) {
+        self.trace_event(|fmt| TraceEvent::Swap {
+            a: fmt(a.as_ref().borrow().value()),
+            b: fmt(b.as_ref().borrow().value())
+        });
+    }
+    pub(super) fn trace_remove(&mut self, x:&NodeRef<K>) {
+        self.trace_event(|fmt| TraceEvent::Remove { key: fmt(x.as_ref().borrow().value()) });
+    }
+}
@@ -0,0 +1,140 @@
+use core::cell::RefCell;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use alloc::rc::{Rc, Weak};
+
+use super::{next_tree_id, Compare, Counters, Iter, Node, NodeRef, RedBlackTree};
+
+impl<K, C: Default> Default for RedBlackTree<K, C> {
+    fn default() -> Self {
+        Self::with_comparator(C::default())
+    }
+}
+
+// 按原样复制整棵树，不共享节点
+fn clone_node<K: Clone>(x:&NodeRef<K>, parent:Option<Weak<RefCell<Node<K>>>>, owner:usize) -> NodeRef<K> {
+    let x_node = x.as_ref().borrow();
+    let n = Rc::new(RefCell::new(Node::new(x_node.value().clone(), x_node.color, parent)));
+    {
+        let mut n_write = n.as_ref().borrow_mut();
+        n_write.owner = owner;
+        n_write.left = x_node.left.as_ref().map(|l| clone_node(l, Some(Rc::downgrade(&n)), owner));
+        n_write.right = x_node.right.as_ref().map(|r| clone_node(r, Some(Rc::downgrade(&n)), owner));
+    }
+    n
+}
+
+impl<K: Clone, C: Clone> Clone for RedBlackTree<K, C> {
+    fn clone(&self) -> Self {
+        let id = next_tree_id();
+        Self {
+            root: self.root.as_ref().map(|x| clone_node(x, None, id)),
+            len: self.len,
+            id,
+            compare: self.compare.clone(),
+            counters: Counters::default(),
+            tracer: None,
+            hooks: None
+        }
+    }
+}
+
+// 和 clear 一样逐个摘下叶子，不触发 on_remove
+impl<K, C> Drop for RedBlackTree<K, C> {
+    fn drop(&mut self) {
+        self.hooks = None;
+        self.clear();
+    }
+}
+
+impl<K, C: Compare<K> + Default> FromIterator<K> for RedBlackTree<K, C> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter:I) -> Self {
+        let mut tree = Self::default();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K, C: Compare<K>> Extend<K> for RedBlackTree<K, C> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter:I) {
+        for value in iter {
+            self.add(value);
+        }
+    }
+}
+
+impl<'a, K: Copy + 'a, C: Compare<K>> Extend<&'a K> for RedBlackTree<K, C> {
+    fn extend<I: IntoIterator<Item = &'a K>>(&mut self, iter:I) {
+        for value in iter {
+            self.add(*value);
+        }
+    }
+}
+
+// 逐个比较中序序列
+fn cmp_by<K, T>(a:Iter<'_, K>, b:Iter<'_, K>, f:impl Fn(&K, &K) -> Option<T>, less:T, greater:T) -> Option<T> {
+    let (mut a, mut b) = (a, b);
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return None,
+            (None, Some(_)) => return Some(less),
+            (Some(_), None) => return Some(greater),
+            (Some(x), Some(y)) => {
+                if let Some(o) = f(x.as_ref().borrow().value(), y.as_ref().borrow().value()) {
+                    return Some(o);
+                }
+            }
+        }
+    }
+}
+
+impl<K: PartialEq, C: Compare<K>> PartialEq for RedBlackTree<K, C> {
+    fn eq(&self, other:&Self) -> bool {
+        self.len == other.len
+            && self.iter().zip(other.iter()).all(|(a, b)| a.as_ref().borrow().value() == b.as_ref().borrow().value())
+    }
+}
+
+impl<K: Eq, C: Compare<K>> Eq for RedBlackTree<K, C> {}
+
+impl<K: PartialOrd, C: Compare<K>> PartialOrd for RedBlackTree<K, C> {
+    fn partial_cmp(&self, other:&Self) -> Option<Ordering> {
+        let f = |a:&K, b:&K| match a.partial_cmp(b) {
+            Some(Ordering::Equal) => None,
+            o => Some(o)
+        };
+        cmp_by(self.iter(), other.iter(), f, Some(Ordering::Less), Some(Ordering::Greater))
+            .unwrap_or(Some(Ordering::Equal))
+    }
+}
+
+impl<K: Ord, C: Compare<K>> Ord for RedBlackTree<K, C> {
+    fn cmp(&self, other:&Self) -> Ordering {
+        let f = |a:&K, b:&K| match a.cmp(b) {
+            Ordering::Equal => None,
+            o => Some(o)
+        };
+        cmp_by(self.iter(), other.iter(), f, Ordering::Less, Ordering::Greater)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl<K: Hash, C: Compare<K>> Hash for RedBlackTree<K, C> {
+    fn hash<H: Hasher>(&self, state:&mut H) {
+        self.len.hash(state);
+        for x in self.iter() {
+            x.as_ref().borrow().value().hash(state);
+        }
+    }
+}
+
+impl<K: fmt::Debug, C: Compare<K>> fmt::Debug for RedBlackTree<K, C> {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut set = f.debug_set();
+        for x in self.iter() {
+            set.entry(x.as_ref().borrow().value());
+        }
+        set.finish()
+    }
+}
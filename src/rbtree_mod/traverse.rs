@@ -0,0 +1,157 @@
+use core::cell::Ref;
+use alloc::collections::VecDeque;
+use core::marker::PhantomData;
+use alloc::vec::Vec;
+
+use super::{Color, NodeRef, RedBlackTree};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Root,
+    Left,
+    Right
+}
+
+/// 遍历时返回的节点信息，depth 从根节点的 0 开始
+pub struct NodeView<K> {
+    node: NodeRef<K>,
+    pub color: Color,
+    pub depth: usize,
+    pub side: Side
+}
+
+impl<K> NodeView<K> {
+    fn new(node:NodeRef<K>, depth:usize, side:Side) -> Self {
+        let color = node.as_ref().borrow().color;
+        Self {
+            node,
+            color,
+            depth,
+            side
+        }
+    }
+    pub fn value(&self) -> Ref<'_, K> {
+        Ref::map(self.node.as_ref().borrow(), |n| n.value())
+    }
+    pub fn node(&self) -> &NodeRef<K> {
+        &self.node
+    }
+    // 子节点按左、右的顺序
+    fn children(&self) -> [Option<NodeView<K>>; 2] {
+        let x_node = self.node.as_ref().borrow();
+        [
+            x_node.left.clone().map(|l| NodeView::new(l, self.depth + 1, Side::Left)),
+            x_node.right.clone().map(|r| NodeView::new(r, self.depth + 1, Side::Right))
+        ]
+    }
+}
+
+pub struct PreOrder<'a, K> {
+    stack: Vec<NodeView<K>>,
+    _marker: PhantomData<&'a K>
+}
+
+impl<'a, K> Iterator for PreOrder<'a, K> {
+    type Item = NodeView<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let x = self.stack.pop()?;
+        let [left, right] = x.children();
+        self.stack.extend(right);
+        self.stack.extend(left);
+        Some(x)
+    }
+}
+
+pub struct PostOrder<'a, K> {
+    // 第二项表示子节点是否已经入栈
+    stack: Vec<(NodeView<K>, bool)>,
+    _marker: PhantomData<&'a K>
+}
+
+impl<'a, K> Iterator for PostOrder<'a, K> {
+    type Item = NodeView<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (x, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some(x);
+            }
+            let [left, right] = x.children();
+            self.stack.push((x, true));
+            self.stack.extend(right.map(|r| (r, false)));
+            self.stack.extend(left.map(|l| (l, false)));
+        }
+    }
+}
+
+pub struct LevelOrder<'a, K> {
+    queue: VecDeque<NodeView<K>>,
+    _marker: PhantomData<&'a K>
+}
+
+impl<'a, K> Iterator for LevelOrder<'a, K> {
+    type Item = NodeView<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let x = self.queue.pop_front()?;
+        let [left, right] = x.children();
+        self.queue.extend(left);
+        self.queue.extend(right);
+        Some(x)
+    }
+}
+
+impl<K, C> RedBlackTree<K, C> {
+    fn root_view(&self) -> Option<NodeView<K>> {
+        self.root.clone().map(|x| NodeView::new(x, 0, Side::Root))
+    }
+    pub fn iter_preorder(&self) -> PreOrder<'_, K> {
+        PreOrder {
+            stack: self.root_view().into_iter().collect(),
+            _marker: PhantomData
+        }
+    }
+    pub fn iter_postorder(&self) -> PostOrder<'_, K> {
+        PostOrder {
+            stack: self.root_view().map(|x| (x, false)).into_iter().collect(),
+            _marker: PhantomData
+        }
+    }
+    pub fn iter_level_order(&self) -> LevelOrder<'_, K> {
+        LevelOrder {
+            queue: self.root_view().into_iter().collect(),
+            _marker: PhantomData
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K> RedBlackTree<K> {
+    // iter_preorder 的逆操作，按先序的 (键, 颜色, 深度, 方向) 直接建树，不检查红黑树性质。
+    // fuzz 用它构造插入删除很难到达的形状
+    pub(crate) fn from_preorder(nodes:impl IntoIterator<Item = (K, Color, usize, Side)>) -> Self {
+        use core::cell::RefCell;
+        use alloc::rc::Rc;
+        use super::Node;
+        let mut tree = Self::new();
+        // 当前节点的所有祖先，下标为深度
+        let mut path: Vec<NodeRef<K>> = Vec::new();
+        for (value, color, depth, side) in nodes {
+            path.truncate(depth);
+            let parent = path.last().cloned();
+            let mut node = Node::new(value, color, parent.as_ref().map(Rc::downgrade));
+            node.owner = tree.id;
+            let x = Rc::new(RefCell::new(node));
+            match (parent, side) {
+                (None, _) => tree.root = Some(x.clone()),
+                (Some(p), Side::Left) => p.as_ref().borrow_mut().left = Some(x.clone()),
+                (Some(p), _) => p.as_ref().borrow_mut().right = Some(x.clone())
+            }
+            tree.len += 1;
+            path.push(x);
+        }
+        tree
+    }
+}
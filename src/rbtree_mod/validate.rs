@@ -0,0 +1,35 @@
+use core::fmt;
+
+use super::{balance, Compare, RedBlackTree};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvariantError {
+    RootNotBlack,
+    RedRed,
+    BlackHeight,
+    Order,
+    BrokenParent,
+    Len { recorded: usize, actual: usize }
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantError::RootNotBlack => write!(f, "root is not black"),
+            InvariantError::RedRed => write!(f, "red node has a red child"),
+            InvariantError::BlackHeight => write!(f, "black height differs between paths"),
+            InvariantError::Order => write!(f, "keys are out of order"),
+            InvariantError::BrokenParent => write!(f, "parent link does not match"),
+            InvariantError::Len { recorded, actual } => write!(f, "len is {} but tree has {} nodes", recorded, actual)
+        }
+    }
+}
+
+impl core::error::Error for InvariantError {}
+
+impl<K, C: Compare<K>> RedBlackTree<K, C> {
+    // 检查全部红黑树性质：根黑、无连续红、黑高一致、中序有序、父指针正确、len 正确
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        balance::validate(self, self.len)
+    }
+}
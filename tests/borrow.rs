@@ -0,0 +1,47 @@
+use std::ops::Bound::{Excluded, Included};
+
+use rb_tree::rbtree_mod::{CompactRedBlackTree, RawRedBlackTree, RedBlackTree};
+
+fn keys(tree:&RedBlackTree<String>) -> Vec<String> {
+    tree.iter().map(|x| x.as_ref().borrow().value().clone()).collect()
+}
+
+#[test]
+fn string_keys_by_str() {
+    let mut tree: RedBlackTree<String> = ["delta", "alpha", "charlie", "bravo", "echo"].iter().map(|s| s.to_string()).collect();
+    assert!(tree.contains("alpha"));
+    assert!(!tree.contains("zulu"));
+    assert_eq!(tree.get("charlie").unwrap().as_ref().borrow().value(), "charlie");
+    assert_eq!(tree.floor("c").unwrap().as_ref().borrow().value(), "bravo");
+    assert_eq!(tree.ceiling("c").unwrap().as_ref().borrow().value(), "charlie");
+    // str 不是 Sized，区间用 Bound 元组表示
+    let mid: Vec<String> = tree.range::<str, _>((Included("b"), Excluded("d"))).map(|x| x.as_ref().borrow().value().clone()).collect();
+    assert_eq!(mid, ["bravo", "charlie"]);
+    assert!(tree.remove("bravo"));
+    assert!(!tree.remove("bravo"));
+    tree.del("echo");
+    assert_eq!(keys(&tree), ["alpha", "charlie", "delta"]);
+    assert_eq!(tree.validate(), Ok(()));
+}
+
+#[test]
+fn vec_keys_by_slice() {
+    let mut tree: RedBlackTree<Vec<u8>> = [b"ab".to_vec(), b"a".to_vec(), b"b".to_vec()].into_iter().collect();
+    assert!(tree.contains(&b"ab"[..]));
+    assert!(tree.remove(&b"a"[..]));
+    assert_eq!(tree.len(), 2);
+    assert_eq!(tree.floor(&b"az"[..]).unwrap().as_ref().borrow().value(), b"ab");
+}
+
+// 兄弟树也接受借用形式
+#[test]
+fn sibling_trees_by_str() {
+    let mut raw: RawRedBlackTree<String> = ["b", "a", "c"].iter().map(|s| s.to_string()).collect();
+    assert_eq!(raw.get("a").map(String::as_str), Some("a"));
+    assert_eq!(raw.take("b").as_deref(), Some("b"));
+    assert_eq!(raw.range::<str, _>((Included("a"), Included("c"))).count(), 2);
+    let mut compact: CompactRedBlackTree<String> = ["b", "a", "c"].iter().map(|s| s.to_string()).collect();
+    assert_eq!(compact.ceiling("bb").map(String::as_str), Some("c"));
+    assert!(compact.remove("c"));
+    assert!(!compact.contains("c"));
+}
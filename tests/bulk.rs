@@ -0,0 +1,74 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rb_tree::rbtree_mod::RedBlackTree;
+
+fn keys(tree:&RedBlackTree<i32>) -> Vec<i32> {
+    tree.iter().map(|x| *x.as_ref().borrow().value()).collect()
+}
+
+#[test]
+fn retain_keeps_matching_keys() {
+    for n in [0, 1, 2, 7, 100, 1000] {
+        let mut tree: RedBlackTree<i32> = (0..n).collect();
+        tree.retain(|k| k % 3 == 1);
+        assert_eq!(keys(&tree), (0..n).filter(|k| k % 3 == 1).collect::<Vec<_>>());
+        assert_eq!(tree.len(), keys(&tree).len());
+        assert_eq!(tree.validate(), Ok(()));
+    }
+}
+
+#[test]
+fn extract_if_yields_removed_keys() {
+    let mut tree: RedBlackTree<i32> = (0..100).collect();
+    let removed: Vec<i32> = tree.extract_if(|k| k % 4 == 0).collect();
+    assert_eq!(removed, (0..100).step_by(4).collect::<Vec<_>>());
+    assert_eq!(keys(&tree), (0..100).filter(|k| k % 4 != 0).collect::<Vec<_>>());
+    assert_eq!(tree.validate(), Ok(()));
+}
+
+#[test]
+fn extract_if_dropped_early_keeps_the_rest() {
+    let mut tree: RedBlackTree<i32> = (0..100).collect();
+    // 只取出前 3 个，剩下没有遍历到的键保留
+    let removed: Vec<i32> = tree.extract_if(|k| k % 2 == 0).take(3).collect();
+    assert_eq!(removed, [0, 2, 4]);
+    assert_eq!(tree.len(), 97);
+    assert!(!tree.contains(&4) && tree.contains(&6));
+    assert_eq!(tree.validate(), Ok(()));
+}
+
+#[test]
+fn drain_empties_the_tree() {
+    let mut tree: RedBlackTree<String> = ["b", "c", "a"].into_iter().map(String::from).collect();
+    let drained = tree.drain();
+    assert_eq!(drained.size_hint(), (3, Some(3)));
+    assert_eq!(drained.collect::<Vec<_>>(), ["a", "b", "c"]);
+    assert!(tree.is_empty());
+    assert_eq!(tree.validate(), Ok(()));
+    tree.add("d".to_string());
+    assert!(tree.contains("d"));
+}
+
+#[test]
+fn bulk_removal_notifies_hooks() {
+    let removed = Rc::new(RefCell::new(vec![]));
+    let mut tree: RedBlackTree<i32> = (0..10).collect();
+    let r = removed.clone();
+    tree.on_remove(move |k| r.borrow_mut().push(*k));
+    tree.retain(|k| *k >= 2);
+    tree.extract_if(|k| *k == 5).for_each(drop);
+    tree.drain().next();
+    assert_eq!(*removed.borrow(), [0, 1, 5, 2, 3, 4, 6, 7, 8, 9]);
+}
+
+// get / 句柄还持有节点时也能取出键
+#[test]
+fn drain_with_live_node() {
+    let mut tree: RedBlackTree<i32> = (0..10).collect();
+    let x = tree.get(&3).unwrap();
+    let h = tree.handle(&7).unwrap();
+    assert_eq!(tree.drain().collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+    assert!(tree.is_empty() && !tree.contains_handle(&h));
+    assert_eq!(Rc::strong_count(&x), 1);
+}
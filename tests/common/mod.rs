@@ -0,0 +1,179 @@
+// 几个测试文件共用的模型对照和计数元素，每个文件只用到其中一部分
+#![allow(dead_code)]
+
+use std::cell::Cell;
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use rb_tree::fuzz::{gen_ops, Config, Op};
+use rb_tree::rbtree_mod::{CapacityFull, CompactRedBlackTree, InvariantError, RawRedBlackTree, StaticRedBlackTree};
+
+/// 被对照的树。add 返回值是否放进了树，只有固定容量的树满了才会是 false
+pub trait TestTree<K> {
+    fn add(&mut self, k:K) -> bool;
+    fn remove(&mut self, k:&K) -> bool;
+    fn get(&self, k:&K) -> Option<K>;
+    fn range(&self, a:&K, b:&K) -> Vec<K>;
+    fn keys(&self) -> Vec<K>;
+    fn len(&self) -> usize;
+    fn validate(&self) -> Result<(), InvariantError>;
+    // 容量上限，没有上限为 None
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl<K: Ord + Clone> TestTree<K> for RawRedBlackTree<K> {
+    fn add(&mut self, k:K) -> bool {
+        RawRedBlackTree::add(self, k);
+        true
+    }
+    fn remove(&mut self, k:&K) -> bool {
+        RawRedBlackTree::remove(self, k)
+    }
+    fn get(&self, k:&K) -> Option<K> {
+        RawRedBlackTree::get(self, k).cloned()
+    }
+    fn range(&self, a:&K, b:&K) -> Vec<K> {
+        RawRedBlackTree::range(self, a..=b).cloned().collect()
+    }
+    fn keys(&self) -> Vec<K> {
+        self.iter().cloned().collect()
+    }
+    fn len(&self) -> usize {
+        RawRedBlackTree::len(self)
+    }
+    fn validate(&self) -> Result<(), InvariantError> {
+        RawRedBlackTree::validate(self)
+    }
+}
+
+impl<K: Ord + Clone> TestTree<K> for CompactRedBlackTree<K> {
+    fn add(&mut self, k:K) -> bool {
+        CompactRedBlackTree::add(self, k);
+        true
+    }
+    fn remove(&mut self, k:&K) -> bool {
+        CompactRedBlackTree::remove(self, k)
+    }
+    fn get(&self, k:&K) -> Option<K> {
+        CompactRedBlackTree::get(self, k).cloned()
+    }
+    fn range(&self, a:&K, b:&K) -> Vec<K> {
+        CompactRedBlackTree::range(self, a..=b).cloned().collect()
+    }
+    fn keys(&self) -> Vec<K> {
+        self.iter().cloned().collect()
+    }
+    fn len(&self) -> usize {
+        CompactRedBlackTree::len(self)
+    }
+    fn validate(&self) -> Result<(), InvariantError> {
+        CompactRedBlackTree::validate(self)
+    }
+}
+
+// 满了以后 add 必须把值原样还回来
+impl<K: Ord + Clone + Debug, const N: usize> TestTree<K> for StaticRedBlackTree<K, N> {
+    fn add(&mut self, k:K) -> bool {
+        match StaticRedBlackTree::add(self, k.clone()) {
+            Ok(()) => true,
+            Err(CapacityFull(v)) => {
+                assert_eq!(v, k);
+                false
+            }
+        }
+    }
+    fn remove(&mut self, k:&K) -> bool {
+        StaticRedBlackTree::remove(self, k)
+    }
+    fn get(&self, k:&K) -> Option<K> {
+        StaticRedBlackTree::get(self, k).cloned()
+    }
+    fn range(&self, a:&K, b:&K) -> Vec<K> {
+        StaticRedBlackTree::range(self, a..=b).cloned().collect()
+    }
+    fn keys(&self) -> Vec<K> {
+        self.iter().cloned().collect()
+    }
+    fn len(&self) -> usize {
+        StaticRedBlackTree::len(self)
+    }
+    fn validate(&self) -> Result<(), InvariantError> {
+        StaticRedBlackTree::validate(self)
+    }
+    fn capacity(&self) -> Option<usize> {
+        Some(N)
+    }
+}
+
+/// 对 tree 执行 gen_ops(seed) 并和 BTreeSet 对照，每一步都检查红黑树性质。key 把生成的 i32 转成树的键
+pub fn check_seed<K: Ord + Clone + Debug>(tree:&mut impl TestTree<K>, seed:u64, config:Config, key:impl Fn(i32) -> K) {
+    let mut model = BTreeSet::new();
+    for (step, op) in gen_ops(seed, config).into_iter().enumerate() {
+        match op {
+            Op::Add(k) => {
+                if tree.add(key(k)) {
+                    model.insert(key(k));
+                } else {
+                    assert!(Some(model.len()) == tree.capacity() && !model.contains(&key(k)), "seed {} step {}", seed, step);
+                }
+            },
+            Op::Del(k) => assert_eq!(tree.remove(&key(k)), model.remove(&key(k)), "seed {} step {}", seed, step),
+            Op::Get(k) => assert_eq!(tree.get(&key(k)), model.get(&key(k)).cloned(), "seed {} step {}", seed, step),
+            Op::Range(a, b) => {
+                let (a, b) = (key(a), key(b));
+                let expected: Vec<K> = if a <= b { model.range(&a..=&b).cloned().collect() } else { vec![] };
+                assert_eq!(tree.range(&a, &b), expected, "seed {} step {}", seed, step);
+            }
+        }
+        if let Err(e) = tree.validate() {
+            panic!("seed {} step {} ({}): {}", seed, step, op, e);
+        }
+        assert_eq!(tree.len(), model.len(), "seed {} step {}", seed, step);
+    }
+    assert_eq!(tree.keys(), model.into_iter().collect::<Vec<K>>());
+}
+
+/// 记录还活着的值的个数，用来检查每个值正好释放一次
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Tracked(pub i32, pub Rc<Cell<i32>>);
+
+impl Tracked {
+    pub fn new(k:i32, live:&Rc<Cell<i32>>) -> Self {
+        live.set(live.get() + 1);
+        Tracked(k, live.clone())
+    }
+}
+
+impl Clone for Tracked {
+    fn clone(&self) -> Self {
+        Tracked::new(self.0, &self.1)
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.1.set(self.1.get() - 1);
+    }
+}
+
+/// 插入、替换、删除 n 个 Tracked 后释放树，每一步活着的值都要和树中的键一样多
+pub fn check_drops<T: TestTree<Tracked>>(mut tree:T, n:i32) {
+    let live = Rc::new(Cell::new(0));
+    for k in 0..n {
+        tree.add(Tracked::new(k, &live));
+    }
+    assert_eq!(live.get() as usize, tree.len());
+    // 替换相同的键时旧值被释放
+    tree.add(Tracked::new(0, &live));
+    assert_eq!(live.get() as usize, tree.len());
+    for k in (0..n).step_by(3) {
+        assert!(tree.remove(&Tracked::new(k, &live)));
+        assert_eq!(live.get() as usize, tree.len());
+    }
+    assert_eq!(tree.validate(), Ok(()));
+    drop(tree);
+    assert_eq!(live.get(), 0);
+}
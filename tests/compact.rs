@@ -0,0 +1,71 @@
+// 用到了 fuzz，需要 std
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::{check_drops, check_seed};
+use rb_tree::fuzz::Config;
+use rb_tree::rbtree_mod::{CompactRedBlackTree, Natural, RedBlackTree, Reverse};
+
+#[test]
+fn differential_small_keys() {
+    for seed in 0..100 {
+        check_seed(&mut CompactRedBlackTree::new(), seed, Config { steps: 1000, keys: 32 }, |k| k);
+    }
+}
+
+#[test]
+fn differential_default() {
+    for seed in 0..20 {
+        check_seed(&mut CompactRedBlackTree::new(), seed, Config::default(), |k| k);
+    }
+}
+
+#[test]
+fn every_value_dropped_once() {
+    check_drops(CompactRedBlackTree::new(), 500);
+}
+
+#[test]
+fn ascending_then_descending_delete() {
+    let mut tree: CompactRedBlackTree<i32> = (0..300).collect();
+    for k in (0..300).rev() {
+        assert_eq!(tree.take(&k), Some(k));
+        assert_eq!(tree.validate(), Ok(()));
+    }
+    assert!(tree.is_empty());
+}
+
+#[test]
+fn floor_ceiling_and_comparator() {
+    let tree: CompactRedBlackTree<i32> = (0..10).map(|k| k * 10).collect();
+    assert_eq!(tree.floor(&35), Some(&30));
+    assert_eq!(tree.floor(&30), Some(&30));
+    assert_eq!(tree.floor(&-1), None);
+    assert_eq!(tree.ceiling(&35), Some(&40));
+    assert_eq!(tree.ceiling(&91), None);
+    assert_eq!((tree.first(), tree.last()), (Some(&0), Some(&90)));
+    assert_eq!(tree.range(25..=30).collect::<Vec<_>>(), [&30]);
+    assert_eq!(tree.range(50..).copied().collect::<Vec<_>>(), [50, 60, 70, 80, 90]);
+    assert_eq!(format!("{:?}", tree.range(..20).collect::<Vec<_>>()), "[0, 10]");
+    let (lo, hi) = (30, 20);
+    assert_eq!(tree.range(lo..hi).count(), 0);
+
+    let mut rev = CompactRedBlackTree::with_comparator(Reverse(Natural));
+    rev.extend([3, 1, 2]);
+    assert_eq!(rev.iter().copied().collect::<Vec<_>>(), [3, 2, 1]);
+    assert_eq!(rev.validate(), Ok(()));
+}
+
+#[test]
+fn compact_nodes_are_smaller() {
+    let rc: RedBlackTree<u32> = (0..1000).collect();
+    let mut compact: CompactRedBlackTree<u32> = (0..1000).collect();
+    compact.shrink_to_fit();
+    let (rc, compact) = (rc.memory_usage(), compact.memory_usage());
+    // 三个 u32 链接加 u32 键，没有填充
+    assert_eq!(compact.node_bytes, 16);
+    assert_eq!(compact.color_bytes, 0);
+    assert!(compact.node_bytes * 3 < rc.node_bytes);
+    assert!(compact.total_bytes * 3 < rc.total_bytes);
+}
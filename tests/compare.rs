@@ -0,0 +1,87 @@
+use std::cmp::Ordering;
+
+use rb_tree::rbtree_mod::{ByKey, Compare, Natural, RedBlackTree, Reverse};
+
+fn keys<K: Clone, C: Compare<K>>(tree:&RedBlackTree<K, C>) -> Vec<K> {
+    tree.iter().map(|x| x.as_ref().borrow().value().clone()).collect()
+}
+
+#[test]
+fn reverse_order() {
+    let mut tree = RedBlackTree::with_comparator(Reverse(Natural));
+    tree.extend(0..100);
+    assert_eq!(keys(&tree), (0..100).rev().collect::<Vec<_>>());
+    assert_eq!(tree.validate(), Ok(()));
+    // floor / ceiling / range 都按比较器的顺序
+    assert_eq!(*tree.floor(&50).unwrap().as_ref().borrow().value(), 50);
+    tree.del(&50);
+    assert_eq!(*tree.floor(&50).unwrap().as_ref().borrow().value(), 51);
+    assert_eq!(*tree.ceiling(&50).unwrap().as_ref().borrow().value(), 49);
+    assert_eq!(tree.range(&12..=&9).map(|x| *x.as_ref().borrow().value()).collect::<Vec<_>>(), [12, 11, 10, 9]);
+    assert_eq!(tree.range(&9..=&12).count(), 0);
+    // 两次反转回到原来的顺序
+    let twice: RedBlackTree<i32, Reverse<Reverse>> = (0..5).rev().collect();
+    assert_eq!(keys(&twice), [0, 1, 2, 3, 4]);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Person {
+    name: &'static str,
+    age: u32
+}
+
+#[test]
+fn by_key_replaces_equal_keys() {
+    let mut tree = RedBlackTree::with_comparator(ByKey(|p:&Person| p.age));
+    tree.add(Person { name: "a", age: 30 });
+    tree.add(Person { name: "b", age: 20 });
+    tree.add(Person { name: "c", age: 40 });
+    // 年龄相同，比较器认为是同一个键，替换原值
+    tree.add(Person { name: "d", age: 30 });
+    assert_eq!(tree.len(), 3);
+    assert_eq!(keys(&tree).iter().map(|p| p.name).collect::<Vec<_>>(), ["b", "d", "c"]);
+    assert!(tree.contains(&Person { name: "?", age: 20 }));
+    assert!(tree.remove(&Person { name: "?", age: 40 }));
+    assert_eq!(tree.validate(), Ok(()));
+}
+
+#[test]
+fn closure_comparator() {
+    // 忽略大小写
+    let mut tree = RedBlackTree::with_comparator(|a:&String, b:&String| a.to_lowercase().cmp(&b.to_lowercase()));
+    for s in ["banana", "Apple", "cherry", "APPLE"] {
+        tree.add(s.to_string());
+    }
+    assert_eq!(keys(&tree), ["APPLE", "banana", "cherry"]);
+    assert!(tree.contains(&"Cherry".to_string()));
+    assert!(tree.remove(&"BANANA".to_string()));
+    assert_eq!(keys(&tree), ["APPLE", "cherry"]);
+}
+
+// 自定义的比较器类型
+#[derive(Default)]
+struct ByLen;
+
+impl Compare<str> for ByLen {
+    fn compare(&self, a:&str, b:&str) -> Ordering {
+        a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+    }
+}
+
+impl Compare<String> for ByLen {
+    fn compare(&self, a:&String, b:&String) -> Ordering {
+        Compare::<str>::compare(self, a, b)
+    }
+}
+
+#[test]
+fn custom_compare_impl() {
+    let mut tree: RedBlackTree<String, ByLen> = ["ccc", "a", "bb", "aa", "b"].iter().map(|s| s.to_string()).collect();
+    assert_eq!(keys(&tree), ["a", "b", "aa", "bb", "ccc"]);
+    for k in 0..200 {
+        tree.add("x".repeat(k % 17) + &k.to_string());
+    }
+    assert_eq!(tree.validate(), Ok(()));
+    let all = keys(&tree);
+    assert!(all.windows(2).all(|w| ByLen.compare(&w[0], &w[1]) == Ordering::Less));
+}
@@ -0,0 +1,56 @@
+// cargo test --features instrument
+#![cfg(feature = "instrument")]
+
+use rb_tree::rbtree_mod::{CounterSnapshot, RedBlackTree, Rotation, TraceEvent};
+
+#[test]
+fn counts_compares_and_resets() {
+    let mut tree = RedBlackTree::new();
+    assert_eq!(tree.counters(), CounterSnapshot::default());
+    tree.add(1);
+    assert_eq!(tree.counters().compare, 0);
+    // 每层比较一次，比较结果同时决定左右
+    tree.add(2);
+    assert_eq!(tree.counters().compare, 1);
+    assert!(tree.contains(&1));
+    assert_eq!(tree.counters().compare, 2);
+    tree.reset_counters();
+    assert_eq!(tree.counters(), CounterSnapshot::default());
+    // 复制出的树从零开始计数
+    tree.add(3);
+    assert_ne!(tree.counters(), CounterSnapshot::default());
+    assert_eq!(tree.clone().counters(), CounterSnapshot::default());
+}
+
+// 计数和 trace 记下的旋转、变色、规则一一对应
+#[test]
+fn counters_agree_with_trace() {
+    let n = 2000;
+    let mut tree = RedBlackTree::new();
+    tree.start_trace();
+    for k in 0..n {
+        tree.add(k * 7919 % n);
+    }
+    for k in 0..n {
+        tree.del(&(k * 104729 % n));
+    }
+    assert!(tree.is_empty());
+    let trace = tree.take_trace().unwrap();
+    let c = tree.counters();
+    let events: Vec<&TraceEvent> = trace.steps.iter().flat_map(|s| s.events.iter()).collect();
+    let rotations = |kind| events.iter().filter(|e| matches!(e, TraceEvent::Rotate { kind: k, .. } if *k == kind)).count() as u64;
+    assert_eq!(c.left_rotate, rotations(Rotation::Left));
+    assert_eq!(c.right_rotate, rotations(Rotation::Right));
+    assert_eq!(c.recolor, events.iter().filter(|e| matches!(e, TraceEvent::Recolor { .. })).count() as u64);
+    let labels = |prefix:&str| trace.steps.iter().filter(|s| s.label.starts_with(prefix)).count() as u64;
+    for i in 0..6 {
+        assert_eq!(c.del_case[i], labels(&format!("del 规则{} ", i + 1)), "del 规则{}", i + 1);
+    }
+    for i in 0..12 {
+        assert_eq!(c.delete_fix_case[i], labels(&format!("delete_fix 规则{} ", i + 1)), "delete_fix 规则{}", i + 1);
+    }
+    // 每次删除至少走一条 del 规则，规则3 和子节点交换后还会再走一条
+    assert!(c.del_case.iter().sum::<u64>() >= n as u64);
+    assert!(c.insert_fix_loop >= labels("insert_fix"));
+    assert!(c.left_rotate > 0 && c.right_rotate > 0 && c.compare > 0);
+}
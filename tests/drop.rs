@@ -0,0 +1,68 @@
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use std::thread;
+
+use rb_tree::rbtree_mod::{Node, RedBlackTree};
+
+const N: u32 = 2_000_000;
+
+// 在栈很小的线程里运行，确认清空和释放不依赖递归
+fn on_small_stack(f:impl FnOnce() + Send + 'static) {
+    thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(f)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+fn sample_nodes(tree:&RedBlackTree<u32>) -> Vec<Weak<RefCell<Node<u32>>>> {
+    (0..N).step_by(997).map(|k| Rc::downgrade(&tree.get(&k).unwrap())).collect()
+}
+
+fn all_freed<T>(nodes:&[Weak<T>]) -> bool {
+    nodes.iter().all(|w| w.strong_count() == 0 && w.weak_count() == 0)
+}
+
+#[test]
+fn clear_millions() {
+    on_small_stack(|| {
+        let mut tree: RedBlackTree<u32> = (0..N).collect();
+        let nodes = sample_nodes(&tree);
+        assert!(!all_freed(&nodes));
+        tree.clear();
+        assert!(tree.is_empty());
+        assert_eq!(tree.validate(), Ok(()));
+        assert!(all_freed(&nodes));
+        // 清空后还能继续使用
+        tree.extend([3, 1, 2]);
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.validate(), Ok(()));
+    });
+}
+
+#[test]
+fn drop_millions() {
+    on_small_stack(|| {
+        let tree: RedBlackTree<u32> = (0..N).rev().collect();
+        let nodes = sample_nodes(&tree);
+        drop(tree);
+        assert!(all_freed(&nodes));
+    });
+}
+
+#[test]
+fn clear_keeps_held_nodes_alive() {
+    let mut tree: RedBlackTree<u32> = (0..100).collect();
+    let held = tree.get(&50).unwrap();
+    let removed = Rc::new(Cell::new(0));
+    let r = removed.clone();
+    tree.on_remove(move |_| r.set(r.get() + 1));
+    tree.clear();
+    assert_eq!(removed.get(), 100);
+    // 外部持有的节点还在，但已经和树断开
+    assert_eq!(*held.as_ref().borrow().value(), 50);
+    assert_eq!(Rc::strong_count(&held), 1);
+    drop(tree);
+    assert_eq!(removed.get(), 100);
+}
@@ -0,0 +1,134 @@
+// 用到了 fuzz，需要 std
+#![cfg(feature = "std")]
+
+mod common;
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use common::{check_drops, check_seed, Tracked};
+use rb_tree::fuzz::{gen_ops, Config, Op};
+use rb_tree::rbtree_mod::{CapacityFull, Natural, RedBlackTree, Reverse, StaticRedBlackTree};
+
+const SEEDS: u64 = if cfg!(miri) { 3 } else { 100 };
+const STEPS: usize = if cfg!(miri) { 300 } else { 1000 };
+
+#[test]
+fn differential_roomy() {
+    for seed in 0..SEEDS {
+        check_seed(&mut StaticRedBlackTree::<i32, 64>::new(), seed, Config { steps: STEPS, keys: 32 }, |k| k);
+    }
+}
+
+#[test]
+fn differential_often_full() {
+    // 容量不够时 add 必须失败、把值还回来且不改变树
+    for seed in 0..SEEDS {
+        check_seed(&mut StaticRedBlackTree::<i32, 16>::new(), seed, Config { steps: STEPS, keys: 40 }, |k| k);
+    }
+}
+
+// 容量足够时和堆上的 RedBlackTree 结果一致
+#[test]
+fn same_order_as_rc_tree() {
+    let mut fixed = StaticRedBlackTree::<i32, 512>::new();
+    let mut rc = RedBlackTree::new();
+    for op in gen_ops(9, Config { steps: STEPS, keys: 500 }) {
+        match op {
+            Op::Add(k) => {
+                fixed.add(k).unwrap();
+                rc.add(k);
+            },
+            Op::Del(k) => {
+                fixed.del(&k);
+                rc.del(&k);
+            },
+            _ => {}
+        }
+    }
+    assert_eq!(fixed.len(), rc.len());
+    assert!(fixed.iter().copied().eq(rc.iter().map(|x| *x.as_ref().borrow().value())));
+    assert_eq!(fixed.range(100..200).count(), rc.range(100..200).count());
+    assert_eq!(fixed.floor(&250), rc.floor(&250).map(|x| *x.as_ref().borrow().value()).as_ref());
+    assert_eq!(fixed.ceiling(&250), rc.ceiling(&250).map(|x| *x.as_ref().borrow().value()).as_ref());
+}
+
+#[test]
+fn capacity_full() {
+    let mut tree = StaticRedBlackTree::<i32, 4>::new();
+    assert_eq!(tree.capacity(), 4);
+    for k in 0..4 {
+        assert_eq!(tree.add(k), Ok(()));
+    }
+    assert!(tree.is_full());
+    assert_eq!(tree.add(10), Err(CapacityFull(10)));
+    // 已有的键可以替换
+    assert_eq!(tree.add(2), Ok(()));
+    assert_eq!(tree.take(&1), Some(1));
+    assert_eq!(tree.add(10), Ok(()));
+    assert_eq!(format!("{:?}", tree), "{0, 2, 3, 10}");
+    assert_eq!(tree.validate(), Ok(()));
+    assert_eq!(CapacityFull(10).to_string(), "tree is full");
+}
+
+#[test]
+fn queries_and_comparator() {
+    let mut tree = StaticRedBlackTree::<i32, 16>::new();
+    for k in 0..10 {
+        tree.add(k * 10).unwrap();
+    }
+    assert_eq!(tree.floor(&35), Some(&30));
+    assert_eq!(tree.floor(&-1), None);
+    assert_eq!(tree.ceiling(&35), Some(&40));
+    assert_eq!(tree.ceiling(&91), None);
+    assert_eq!((tree.first(), tree.last()), (Some(&0), Some(&90)));
+    assert_eq!(tree.range(25..=50).copied().collect::<Vec<_>>(), [30, 40, 50]);
+
+    let mut rev = StaticRedBlackTree::<i32, 3, _>::with_comparator(Reverse(Natural));
+    for k in [1, 3, 2] {
+        rev.add(k).unwrap();
+    }
+    assert_eq!(rev.iter().copied().collect::<Vec<_>>(), [3, 2, 1]);
+    let copy = rev.clone();
+    rev.clear();
+    assert!(rev.is_empty());
+    assert_eq!(copy.len(), 3);
+    assert_eq!(copy.validate(), Ok(()));
+}
+
+#[test]
+fn no_heap_and_const_new() {
+    static EMPTY: StaticRedBlackTree<u64, 8> = StaticRedBlackTree::new();
+    assert!(EMPTY.is_empty());
+    let tree = StaticRedBlackTree::<u64, 1000>::new();
+    let usage = tree.memory_usage();
+    assert_eq!(usage.total_bytes, std::mem::size_of_val(&tree));
+    assert_eq!(usage.node_bytes, 24);
+}
+
+#[test]
+fn every_value_dropped_once() {
+    let live = Rc::new(Cell::new(0));
+    let mut tree = StaticRedBlackTree::<Tracked, 32>::new();
+    for k in 0..32 {
+        tree.add(Tracked::new(k, &live)).unwrap();
+    }
+    let Err(CapacityFull(rejected)) = tree.add(Tracked::new(99, &live)) else {
+        panic!("tree should be full");
+    };
+    drop(rejected);
+    tree.add(Tracked::new(5, &live)).unwrap();
+    assert_eq!(live.get(), 32);
+    for k in (0..32).step_by(2) {
+        tree.del(&Tracked::new(k, &live));
+    }
+    assert_eq!(live.get(), 16);
+    for k in 100..110 {
+        tree.add(Tracked::new(k, &live)).unwrap();
+    }
+    assert_eq!(live.get(), 26);
+    drop(tree);
+    assert_eq!(live.get(), 0);
+    // 装满后替换已有的键
+    check_drops(StaticRedBlackTree::<Tracked, 32>::new(), 32);
+}
@@ -0,0 +1,99 @@
+// 用到了 fuzz / oplog，需要 std
+#![cfg(feature = "std")]
+
+use std::collections::HashSet;
+
+use rb_tree::fuzz::{all_trees, check_ops, exhaustive, exhaustive_shapes, gen_ops, run_seed, shrink, shrink_by, to_test, Config, Op};
+
+#[test]
+fn differential_small_keys() {
+    let config = Config { steps: 1000, keys: 32 };
+    for seed in 0..100 {
+        if let Err(f) = run_seed(seed, config) {
+            panic!("seed {}: {}", seed, f);
+        }
+    }
+}
+
+#[test]
+fn differential_default() {
+    for seed in 0..20 {
+        if let Err(f) = run_seed(seed, Config::default()) {
+            panic!("seed {}: {}", seed, f);
+        }
+    }
+}
+
+#[test]
+fn gen_ops_is_deterministic() {
+    let config = Config { steps: 200, keys: 50 };
+    assert_eq!(gen_ops(7, config), gen_ops(7, config));
+    assert_ne!(gen_ops(7, config), gen_ops(8, config));
+}
+
+#[test]
+fn ascending_then_descending_delete() {
+    let mut ops: Vec<Op> = (0..300).map(Op::Add).collect();
+    ops.extend((0..300).rev().map(Op::Del));
+    assert_eq!(check_ops(&ops), Ok(()));
+}
+
+#[test]
+fn shrink_finds_minimal_sequence() {
+    let mut ops = gen_ops(3, Config { steps: 500, keys: 20 });
+    ops.insert(100, Op::Add(1000));
+    ops.insert(400, Op::Del(1000));
+    // 同时含有这两个操作才算失败
+    let fails = |ops:&[Op]| ops.contains(&Op::Add(1000)) && ops.contains(&Op::Del(1000));
+    assert_eq!(shrink_by(&ops, fails), vec![Op::Add(1000), Op::Del(1000)]);
+}
+
+#[test]
+fn shrink_keeps_passing_sequence() {
+    let ops = gen_ops(4, Config { steps: 50, keys: 10 });
+    assert_eq!(shrink(&ops), ops);
+}
+
+#[test]
+fn to_test_output() {
+    let code = to_test("regression", &[Op::Add(1), Op::Del(1), Op::Range(0, 2)]);
+    assert!(code.starts_with("#[test]\nfn regression() {"));
+    assert!(code.contains("Add(1), Del(1), Range(0, 2),"));
+}
+
+#[test]
+fn exhaustive_small_trees() {
+    for n in 1..=7 {
+        if let Err(ops) = exhaustive(n) {
+            panic!("n = {}\n{}", n, to_test("exhaustive", &shrink(&ops)));
+        }
+    }
+    let coverage = exhaustive(8).unwrap();
+    let del_missing: Vec<String> = coverage.missing().into_iter().filter(|c| c.starts_with("del ")).collect();
+    assert!(del_missing.is_empty(), "{}", coverage);
+}
+
+#[test]
+fn all_trees_are_valid_and_distinct() {
+    let trees = all_trees(9);
+    let mut seen = HashSet::new();
+    for tree in &trees {
+        assert_eq!(tree.validate(), Ok(()), "\n{}", tree.frame().to_ascii());
+        assert!(tree.len() <= 9);
+        assert!(tree.iter().map(|x| *x.borrow().value()).eq(0..tree.len() as i32));
+        assert!(seen.insert(tree.frame().to_ascii()));
+    }
+    // 1 个节点只有黑根，2 个节点为黑根加左或右的红子节点
+    assert_eq!(all_trees(1).len(), 1);
+    assert_eq!(all_trees(2).len(), 3);
+}
+
+#[test]
+fn every_delete_case_is_covered() {
+    let mut coverage = exhaustive(8).unwrap();
+    match exhaustive_shapes(15) {
+        Ok(shapes) => coverage.merge(shapes),
+        Err((tree, k)) => panic!("del {} failed on\n{}", k, tree)
+    }
+    assert_eq!(coverage.missing(), Vec::<String>::new(), "{}", coverage);
+}
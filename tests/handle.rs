@@ -0,0 +1,170 @@
+// 用到了 fuzz，需要 std
+#![cfg(feature = "std")]
+
+use std::collections::BTreeMap;
+
+use rb_tree::fuzz::{gen_ops, Config, Op};
+use rb_tree::rbtree_mod::{Handle, RedBlackTree};
+
+fn keys(tree:&RedBlackTree<i32>) -> Vec<i32> {
+    tree.iter().map(|x| *x.as_ref().borrow().value()).collect()
+}
+
+// 每次操作后，所有还在树中的键的句柄都必须仍然指向自己的键
+fn check_seed(seed:u64, config:Config) {
+    let mut tree = RedBlackTree::new();
+    let mut handles: BTreeMap<i32, Handle<i32>> = BTreeMap::new();
+    let mut removed = vec![];
+    for (step, op) in gen_ops(seed, config).into_iter().enumerate() {
+        match op {
+            Op::Add(k) => {
+                let h = tree.add_handle(k);
+                if let Some(old) = handles.insert(k, h.clone()) {
+                    // 替换相等的键时沿用原节点
+                    assert_eq!(old, h, "seed {} step {}", seed, step);
+                }
+            },
+            // 交替用键和句柄删除
+            Op::Del(k) => match handles.remove(&k) {
+                Some(h) if step % 2 == 0 => {
+                    assert!(tree.remove_handle(&h), "seed {} step {}", seed, step);
+                    removed.push(h);
+                },
+                Some(h) => {
+                    assert!(tree.remove(&k), "seed {} step {}", seed, step);
+                    removed.push(h);
+                },
+                None => assert!(!tree.remove(&k), "seed {} step {}", seed, step)
+            },
+            Op::Get(k) => assert_eq!(tree.handle(&k).as_ref(), handles.get(&k), "seed {} step {}", seed, step),
+            Op::Range(..) => {}
+        }
+        if let Err(e) = tree.validate() {
+            panic!("seed {} step {} ({}): {}", seed, step, op, e);
+        }
+        for (k, h) in &handles {
+            assert_eq!(*h.value(), *k, "seed {} step {}", seed, step);
+            assert!(tree.contains_handle(h));
+        }
+    }
+    for h in &removed {
+        assert!(!tree.contains_handle(h));
+        assert!(!tree.remove_handle(h));
+    }
+}
+
+#[test]
+fn handles_survive_deletes() {
+    for seed in 0..100 {
+        check_seed(seed, Config { steps: 1000, keys: 32 });
+    }
+    for seed in 0..10 {
+        check_seed(seed, Config::default());
+    }
+}
+
+#[test]
+fn get_result_keeps_its_key() {
+    // 以前删除 1 会把后继的值搬进 get(&1) 之外的节点，现在 get 拿到的节点一直是同一个键
+    let mut tree = RedBlackTree::new();
+    for k in 0..64 {
+        tree.add(k);
+    }
+    let nodes: Vec<_> = (0..64).map(|k| tree.get(&k).unwrap()).collect();
+    for k in (0..64).step_by(3) {
+        tree.del(&k);
+    }
+    for (k, x) in nodes.iter().enumerate() {
+        assert_eq!(*x.as_ref().borrow().value(), k as i32);
+        assert_eq!(tree.contains_handle(&Handle::from(x.clone())), k % 3 != 0);
+    }
+    assert_eq!(tree.validate(), Ok(()));
+}
+
+#[test]
+fn walk_with_next_and_prev() {
+    let mut tree: RedBlackTree<i32> = (0..100).collect();
+    let mut forward = vec![];
+    let mut h = tree.first_handle();
+    while let Some(x) = h {
+        forward.push(*x.value());
+        h = tree.next(&x);
+    }
+    assert_eq!(forward, keys(&tree));
+
+    let mut backward = vec![];
+    let mut h = tree.last_handle();
+    while let Some(x) = h {
+        backward.push(*x.value());
+        h = tree.prev(&x);
+    }
+    backward.reverse();
+    assert_eq!(backward, forward);
+
+    // 边走边删除偶数
+    let mut h = tree.first_handle();
+    while let Some(x) = h {
+        h = tree.next(&x);
+        if *x.value() % 2 == 0 {
+            assert!(tree.remove_handle(&x));
+        }
+    }
+    assert_eq!(keys(&tree), (1..100).step_by(2).collect::<Vec<_>>());
+    assert_eq!(tree.validate(), Ok(()));
+}
+
+#[test]
+fn handles_survive_rebuild_and_clear() {
+    let mut tree: RedBlackTree<i32> = (0..50).collect();
+    let handles: Vec<_> = (0..50).map(|k| tree.handle(&k).unwrap()).collect();
+    tree.retain(|k| k % 5 != 0);
+    for (k, h) in handles.iter().enumerate() {
+        assert_eq!(*h.value(), k as i32);
+        assert_eq!(tree.contains_handle(h), k % 5 != 0);
+    }
+    assert_eq!(tree.next(&handles[4]), Some(handles[6].clone()));
+    tree.clear();
+    assert!(handles.iter().all(|h| !tree.contains_handle(h)));
+}
+
+#[test]
+fn removed_handle_has_no_neighbours() {
+    let mut tree: RedBlackTree<i32> = (0..10).collect();
+    let h = tree.handle(&3).unwrap();
+    tree.remove_handle(&h);
+    assert_eq!(tree.next(&h), None);
+    assert_eq!(tree.prev(&h), None);
+}
+
+// release 构建下也要检查，别的树的句柄不能改动任何一棵树
+#[test]
+fn foreign_handle_is_rejected() {
+    let mut a: RedBlackTree<i32> = (0..10).collect();
+    let b: RedBlackTree<i32> = (100..110).collect();
+    for k in [101, 105, 109] {
+        let h = b.handle(&k).unwrap();
+        assert!(!a.contains_handle(&h));
+        assert!(!a.remove_handle(&h));
+        assert_eq!(a.next(&h), None);
+        assert_eq!(a.prev(&h), None);
+    }
+    // 根节点没有父节点，也不能被当成 a 的节点
+    let single: RedBlackTree<i32> = [7].into_iter().collect();
+    assert!(!a.remove_handle(&single.first_handle().unwrap()));
+    assert_eq!((a.len(), b.len()), (10, 10));
+    assert_eq!((a.validate(), b.validate()), (Ok(()), Ok(())));
+}
+
+// 复制出的树有自己的编号，拆下的节点不再属于任何树
+#[test]
+fn clone_and_drain_change_ownership() {
+    let mut a: RedBlackTree<i32> = (0..10).collect();
+    let b = a.clone();
+    let (ha, hb) = (a.handle(&4).unwrap(), b.handle(&4).unwrap());
+    assert!(a.contains_handle(&ha) && !a.contains_handle(&hb));
+    assert!(b.contains_handle(&hb) && !b.contains_handle(&ha));
+    drop(a.drain());
+    assert!(!a.contains_handle(&ha));
+    a.add(4);
+    assert!(!a.contains_handle(&ha));
+}
@@ -0,0 +1,106 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rb_tree::rbtree_mod::{Color, RedBlackTree, Rotation, TraceEvent};
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+// 把四种回调都记到同一个日志里
+fn watch(tree:&mut RedBlackTree<i32>) -> Log {
+    let log = Log::default();
+    let l = log.clone();
+    tree.on_insert(move |k| l.borrow_mut().push(format!("insert {}", k)));
+    let l = log.clone();
+    tree.on_remove(move |k| l.borrow_mut().push(format!("remove {}", k)));
+    let l = log.clone();
+    tree.on_rotate(move |kind, k| l.borrow_mut().push(format!("rotate {:?} {}", kind, k)));
+    let l = log.clone();
+    tree.on_recolor(move |k, from, to| l.borrow_mut().push(format!("recolor {} {:?} {:?}", k, from, to)));
+    log
+}
+
+#[test]
+fn insert_and_remove() {
+    let mut tree = RedBlackTree::new();
+    let log = watch(&mut tree);
+    tree.add(1);
+    // 替换已有的键也触发 on_insert
+    tree.add(1);
+    assert!(!tree.remove(&2));
+    assert!(tree.remove(&1));
+    // 第一个节点直接成为黑色的根，不经过变色
+    assert_eq!(*log.borrow(), ["insert 1", "insert 1", "remove 1"]);
+}
+
+#[test]
+fn rotations_and_recolors_match_trace() {
+    let mut tree = RedBlackTree::new();
+    let log = watch(&mut tree);
+    tree.start_trace();
+    for k in 0..500 {
+        tree.add(k * 37 % 500);
+    }
+    for k in 0..250 {
+        tree.del(&(k * 11 % 500));
+    }
+    let trace = tree.take_trace().unwrap();
+    let expected: Vec<String> = trace.steps.iter()
+        .flat_map(|s| s.events.iter())
+        .filter_map(|e| match e {
+            TraceEvent::Rotate { kind, pivot } => Some(format!("rotate {:?} {}", kind, pivot)),
+            TraceEvent::Recolor { key, from, to } => Some(format!("recolor {} {:?} {:?}", key, from, to)),
+            TraceEvent::Remove { key } => Some(format!("remove {}", key)),
+            TraceEvent::Swap { .. } => None
+        })
+        .collect();
+    let structural: Vec<String> = log.borrow().iter().filter(|e| !e.starts_with("insert")).cloned().collect();
+    assert_eq!(structural, expected);
+    assert_eq!(log.borrow().iter().filter(|e| e.starts_with("insert")).count(), 500);
+    assert_eq!(log.borrow().iter().filter(|e| e.starts_with("remove")).count(), 250);
+}
+
+#[test]
+fn rotate_reports_the_rising_node() {
+    let mut tree = RedBlackTree::new();
+    let rotations = Rc::new(RefCell::new(vec![]));
+    let r = rotations.clone();
+    tree.on_rotate(move |kind, k:&i32| r.borrow_mut().push((kind, *k)));
+    tree.extend([1, 2, 3]);
+    assert_eq!(*rotations.borrow(), [(Rotation::Left, 2)]);
+    assert_eq!(*tree.get(&2).unwrap().as_ref().borrow().value(), 2);
+    assert_eq!(tree.get(&2).unwrap().as_ref().borrow().color(), Color::Black);
+}
+
+#[test]
+fn bulk_removal_and_clear_hooks() {
+    let mut tree: RedBlackTree<i32> = (0..10).collect();
+    let log = watch(&mut tree);
+    tree.retain(|k| k % 2 == 0);
+    assert_eq!(log.borrow().iter().filter(|e| e.starts_with("remove")).count(), 5);
+    log.borrow_mut().clear();
+    tree.clear();
+    let mut removed: Vec<String> = log.borrow().clone();
+    removed.sort();
+    assert_eq!(removed, ["remove 0", "remove 2", "remove 4", "remove 6", "remove 8"]);
+    // 取消后不再触发，drop 也不触发
+    tree.extend(0..10);
+    log.borrow_mut().clear();
+    tree.clear_hooks();
+    tree.add(100);
+    tree.del(&3);
+    drop(tree);
+    assert!(log.borrow().is_empty());
+}
+
+// 每次回调都对应一次真实的旋转，不成立的旋转不上报
+#[test]
+fn ascending_inserts_rotate_exactly() {
+    let mut tree = RedBlackTree::new();
+    let rotations = Rc::new(RefCell::new(vec![]));
+    let r = rotations.clone();
+    tree.on_rotate(move |kind, k:&i32| r.borrow_mut().push((kind, *k)));
+    tree.extend(1..=10);
+    use Rotation::Left;
+    assert_eq!(*rotations.borrow(), [(Left, 2), (Left, 4), (Left, 6), (Left, 4), (Left, 8), (Left, 6)]);
+    assert_eq!(tree.iter_preorder().next().map(|r| *r.value()), Some(6));
+}
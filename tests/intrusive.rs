@@ -0,0 +1,203 @@
+// 用到了 fuzz，需要 std
+#![cfg(feature = "std")]
+
+mod common;
+
+use std::borrow::Borrow;
+use std::cell::Cell;
+use std::pin::{pin, Pin};
+
+use common::{check_seed, TestTree};
+use rb_tree::fuzz::Config;
+use rb_tree::impl_linked;
+use rb_tree::rbtree_mod::{ByKey, IntrusiveRedBlackTree, InvariantError, Link};
+
+const SEEDS: u64 = if cfg!(miri) { 3 } else { 100 };
+const STEPS: usize = if cfg!(miri) { 300 } else { 2000 };
+
+// 按 key 排序，可以直接用 &i32 查找
+struct Item {
+    key: i32,
+    link: Link
+}
+
+impl_linked!(Item, link);
+
+impl Item {
+    fn new(key:i32) -> Self {
+        Item { key, link: Link::new() }
+    }
+}
+
+impl PartialEq for Item {
+    fn eq(&self, other:&Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Item {}
+
+impl PartialOrd for Item {
+    fn partial_cmp(&self, other:&Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Item {
+    fn cmp(&self, other:&Self) -> std::cmp::Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl Borrow<i32> for Item {
+    fn borrow(&self) -> &i32 {
+        &self.key
+    }
+}
+
+fn keys<'a>(it:impl Iterator<Item = &'a Item>) -> Vec<i32> {
+    it.map(|x| x.key).collect()
+}
+
+// 每个键一个元素，元素比树活得久
+struct Pinned<'a> {
+    items: &'a [Pin<Box<Item>>],
+    tree: IntrusiveRedBlackTree<'a, Item>
+}
+
+impl TestTree<i32> for Pinned<'_> {
+    fn add(&mut self, k:i32) -> bool {
+        let item = self.items[k as usize].as_ref();
+        // 已经在树上的元素不能再插入
+        if !item.link.is_linked() {
+            assert!(self.tree.insert(item));
+        }
+        true
+    }
+    fn remove(&mut self, k:&i32) -> bool {
+        self.tree.remove(k).is_some()
+    }
+    fn get(&self, k:&i32) -> Option<i32> {
+        self.tree.get(k).map(|x| x.key)
+    }
+    fn range(&self, a:&i32, b:&i32) -> Vec<i32> {
+        keys(self.tree.range(*a..=*b))
+    }
+    fn keys(&self) -> Vec<i32> {
+        keys(self.tree.iter())
+    }
+    fn len(&self) -> usize {
+        self.tree.len()
+    }
+    fn validate(&self) -> Result<(), InvariantError> {
+        self.tree.validate()
+    }
+}
+
+#[test]
+fn differential() {
+    for seed in 0..SEEDS {
+        let config = Config { steps: STEPS, keys: 32 };
+        let items: Vec<Pin<Box<Item>>> = (0..config.keys).map(|k| Box::pin(Item::new(k))).collect();
+        let mut tree = Pinned { items: &items, tree: IntrusiveRedBlackTree::new() };
+        check_seed(&mut tree, seed, config, |k| k);
+        // 树释放后所有元素都摘下
+        drop(tree);
+        assert!(items.iter().all(|x| !x.link.is_linked()));
+    }
+}
+
+#[test]
+fn stack_pinned_elements() {
+    let a = pin!(Item::new(1));
+    let b = pin!(Item::new(2));
+    let c = pin!(Item::new(3));
+    let dup = pin!(Item::new(2));
+    let mut tree = IntrusiveRedBlackTree::new();
+    assert!(tree.insert(c.as_ref()));
+    assert!(tree.insert(a.as_ref()));
+    assert!(tree.insert(b.as_ref()));
+    // 相等的键不插入，元素保持未链接
+    assert!(!tree.insert(dup.as_ref()));
+    assert!(!dup.link.is_linked());
+    assert_eq!(keys(tree.iter()), [1, 2, 3]);
+
+    assert_eq!(tree.next(&b).map(|x| x.key), Some(3));
+    assert_eq!(tree.prev(&b).map(|x| x.key), Some(1));
+    assert!(tree.next(&c).is_none());
+    assert_eq!(tree.floor(&5).map(|x| x.key), Some(3));
+    assert_eq!(tree.ceiling(&0).map(|x| x.key), Some(1));
+
+    assert!(tree.unlink(&b));
+    assert!(!tree.unlink(&b));
+    assert!(!b.link.is_linked());
+    assert_eq!(tree.next(&a).map(|x| x.key), Some(3));
+    assert_eq!(tree.validate(), Ok(()));
+    // 摘下后可以再插入
+    assert!(tree.insert(b.as_ref()));
+    assert_eq!(format!("{:?}", tree.iter().map(|x| x.key).collect::<Vec<_>>()), "[1, 2, 3]");
+    tree.clear();
+    assert!(tree.is_empty());
+    assert!(!a.link.is_linked() && !b.link.is_linked() && !c.link.is_linked());
+}
+
+#[test]
+fn two_trees() {
+    let items: Vec<Pin<Box<Item>>> = (0..10).map(|k| Box::pin(Item::new(k))).collect();
+    let mut even = IntrusiveRedBlackTree::new();
+    // 降序
+    let mut odd = IntrusiveRedBlackTree::with_comparator(|a:&Item, b:&Item| b.key.cmp(&a.key));
+    for x in &items {
+        if x.key % 2 == 0 {
+            even.insert(x.as_ref());
+        } else {
+            odd.insert(x.as_ref());
+        }
+    }
+    assert_eq!(keys(even.iter()), [0, 2, 4, 6, 8]);
+    assert_eq!(keys(odd.iter()), [9, 7, 5, 3, 1]);
+    // 不属于这棵树的元素
+    assert!(!even.unlink(&items[3]));
+    assert_eq!(odd.len(), 5);
+    assert!(odd.unlink(&items[3]));
+    even.insert(items[3].as_ref());
+    assert_eq!(keys(even.iter()), [0, 2, 3, 4, 6, 8]);
+    assert_eq!((even.validate(), odd.validate()), (Ok(()), Ok(())));
+}
+
+#[test]
+#[should_panic(expected = "already linked")]
+fn insert_linked_element_panics() {
+    let x = pin!(Item::new(1));
+    let mut a = IntrusiveRedBlackTree::new();
+    let mut b = IntrusiveRedBlackTree::new();
+    a.insert(x.as_ref());
+    b.insert(x.as_ref());
+}
+
+// 元素可以有内部可变的其他字段，树只碰 link
+struct Timer {
+    deadline: u64,
+    fired: Cell<bool>,
+    link: Link
+}
+
+impl_linked!(Timer, link);
+
+#[test]
+fn timer_queue() {
+    let timers: Vec<Pin<Box<Timer>>> = [30, 10, 20, 50, 40]
+        .into_iter()
+        .map(|deadline| Box::pin(Timer { deadline, fired: Cell::new(false), link: Link::new() }))
+        .collect();
+    let mut queue = IntrusiveRedBlackTree::with_comparator(ByKey(|t:&Timer| t.deadline));
+    queue.extend(timers.iter().map(|t| t.as_ref()));
+    // 触发 deadline 不超过 35 的定时器
+    while let Some(t) = queue.first().filter(|t| t.deadline <= 35) {
+        t.fired.set(true);
+        queue.unlink(t);
+    }
+    let fired: Vec<u64> = timers.iter().filter(|t| t.fired.get()).map(|t| t.deadline).collect();
+    assert_eq!(fired, [30, 10, 20]);
+    assert_eq!(queue.iter().map(|t| t.deadline).collect::<Vec<_>>(), [40, 50]);
+}
@@ -0,0 +1,62 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering::Relaxed};
+
+use rb_tree::rbtree_mod::RedBlackTree;
+
+// 测试框架的其他线程也会分配，只统计打开了 COUNTING 的线程
+struct Counting;
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn counting() -> bool {
+    COUNTING.try_with(|c| c.get()).unwrap_or(false)
+}
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+static LIVE: AtomicIsize = AtomicIsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout:Layout) -> *mut u8 {
+        if counting() {
+            ALLOCS.fetch_add(1, Relaxed);
+            LIVE.fetch_add(layout.size() as isize, Relaxed);
+        }
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr:*mut u8, layout:Layout) {
+        if counting() {
+            LIVE.fetch_sub(layout.size() as isize, Relaxed);
+        }
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[test]
+fn memory_usage_matches_allocations() {
+    COUNTING.with(|c| c.set(true));
+    let mut tree = RedBlackTree::new();
+    let (allocs, live) = (ALLOCS.load(Relaxed), LIVE.load(Relaxed));
+    for i in 0..1000u64 {
+        tree.add(i);
+    }
+    let usage = tree.memory_usage();
+    // 每次 add 正好一次分配
+    assert_eq!(ALLOCS.load(Relaxed) - allocs, 1000);
+    assert_eq!((LIVE.load(Relaxed) - live) as usize, usage.len * usage.node_bytes);
+    assert_eq!(usage.total_bytes, usage.tree_bytes + 1000 * usage.node_bytes);
+    assert_eq!(usage.value_bytes, 8);
+    assert_eq!(usage.node_bytes, usage.value_bytes + usage.overhead_per_node());
+
+    let live = LIVE.load(Relaxed);
+    for i in 0..500u64 {
+        tree.del(&i);
+    }
+    assert_eq!((live - LIVE.load(Relaxed)) as usize, 500 * usage.node_bytes);
+    assert_eq!(tree.memory_usage().total_bytes, usage.tree_bytes + 500 * usage.node_bytes);
+}
@@ -0,0 +1,91 @@
+// 用到了 fuzz / oplog，需要 std
+#![cfg(feature = "std")]
+
+use std::io::Cursor;
+
+use rb_tree::fuzz::{gen_ops, Config, Op};
+use rb_tree::oplog::{escape_key, replay, unescape_key, Recorder, ReplayError, HEADER};
+use rb_tree::rbtree_mod::RedBlackTree;
+
+#[test]
+fn record_then_replay() {
+    let ops = gen_ops(1, Config { steps: 2000, keys: 300 });
+    let checks = ops.iter().filter(|op| matches!(op, Op::Range(..))).count() as u64;
+    let mut r = Recorder::new(RedBlackTree::new(), vec![]);
+    for op in ops {
+        match op {
+            Op::Add(k) => r.add(k),
+            Op::Del(k) => r.del(&k),
+            Op::Get(k) => {
+                r.contains(&k);
+            },
+            Op::Range(a, b) => {
+                assert_eq!(r.range(a..=b).count(), r.tree().range(a..=b).count());
+                r.checkpoint();
+            }
+        }
+    }
+    let (tree, log) = r.finish().unwrap();
+    assert!(log.starts_with(HEADER.as_bytes()));
+
+    let (replayed, report) = replay::<i32>(Cursor::new(log), 100).unwrap();
+    assert_eq!(replayed, tree);
+    assert_eq!(report.len, tree.len());
+    assert_eq!(report.add.count + report.del.count + report.get.count, 2000 - checks);
+    assert_eq!(report.range.count, checks);
+    // 日志中的 check 行、每 100 个操作一次、结束时一次
+    assert_eq!(report.checkpoints, checks + 2000 / 100 + 1);
+}
+
+#[test]
+fn replay_reports_bad_line() {
+    let log = "# comment\nadd 1\nadd x\n";
+    match replay::<i32>(Cursor::new(log), 0) {
+        Err(ReplayError::Parse { line, .. }) => assert_eq!(line, 3),
+        other => panic!("{:?}", other.map(|(_, r)| r))
+    }
+}
+
+#[test]
+fn queries_are_logged() {
+    let mut r = Recorder::new((0..10).collect::<RedBlackTree<i32>>(), vec![]);
+    assert_eq!(r.floor(&-1), None);
+    assert!(r.ceiling(&3).is_some());
+    assert_eq!(r.range(2..5).count(), 3);
+    assert_eq!(r.range(..=4).count(), 5);
+    assert_eq!(r.range((std::ops::Bound::Excluded(7), std::ops::Bound::Unbounded)).count(), 2);
+    let (_, log) = r.finish().unwrap();
+    let log = String::from_utf8(log).unwrap();
+    assert_eq!(log.lines().skip(1).collect::<Vec<_>>(), ["floor -1", "ceiling 3", "range [2 5)", "range * 4]", "range (7 *"]);
+
+    let (_, report) = replay::<i32>(Cursor::new(log), 0).unwrap();
+    assert_eq!((report.floor.count, report.ceiling.count, report.range.count), (1, 1, 3));
+}
+
+#[test]
+fn keys_with_whitespace_round_trip() {
+    let keys = [" lead", "trail ", "in ner", "new\nline", "tab\t", "back\\slash", "", "*", "a]", "[b"];
+    for k in keys {
+        assert!(!escape_key(k).contains(char::is_whitespace));
+        assert_eq!(unescape_key(&escape_key(k)).as_deref(), Some(k));
+    }
+    let mut r = Recorder::new(RedBlackTree::new(), vec![]);
+    for k in keys {
+        r.add(k.to_string());
+    }
+    r.del(&"in ner".to_string());
+    assert_eq!(r.range(" lead".to_string()..="trail ".to_string()).count(), 8);
+    let (tree, log) = r.finish().unwrap();
+    let (replayed, report) = replay::<String>(Cursor::new(log), 1).unwrap();
+    assert_eq!(replayed, tree);
+    assert_eq!(replayed.len(), keys.len() - 1);
+    assert_eq!(report.range.count, 1);
+}
+
+#[test]
+fn replay_rejects_unknown_escape() {
+    match replay::<String>(Cursor::new("add a\\qb\n"), 0) {
+        Err(ReplayError::Parse { line, .. }) => assert_eq!(line, 1),
+        other => panic!("{:?}", other.map(|(_, r)| r))
+    }
+}
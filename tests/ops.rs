@@ -0,0 +1,66 @@
+use std::collections::BTreeSet;
+
+use rb_tree::rbtree_mod::{Compare, Natural, RedBlackTree, Reverse};
+
+fn keys<C: Compare<i32>>(tree:&RedBlackTree<i32, C>) -> Vec<i32> {
+    tree.iter().map(|x| *x.as_ref().borrow().value()).collect()
+}
+
+// 和 BTreeSet 的同名运算对照
+#[test]
+fn operators_match_btreeset() {
+    let sets: Vec<Vec<i32>> = vec![
+        vec![],
+        vec![7],
+        (0..50).step_by(2).collect(),
+        (0..50).step_by(3).collect(),
+        (25..100).collect(),
+        (0..200).rev().step_by(7).collect()
+    ];
+    for x in &sets {
+        for y in &sets {
+            let (a, b): (RedBlackTree<i32>, RedBlackTree<i32>) = (x.iter().copied().collect(), y.iter().copied().collect());
+            let (sa, sb): (BTreeSet<i32>, BTreeSet<i32>) = (x.iter().copied().collect(), y.iter().copied().collect());
+            let expected = |s:BTreeSet<i32>| s.into_iter().collect::<Vec<_>>();
+            for (tree, set) in [(&a | &b, &sa | &sb), (&a & &b, &sa & &sb), (&a - &b, &sa - &sb), (&a ^ &b, &sa ^ &sb)] {
+                assert_eq!(tree.validate(), Ok(()));
+                assert_eq!(tree.len(), set.len());
+                assert_eq!(keys(&tree), expected(set));
+            }
+            let mut c = a.clone();
+            c |= &b;
+            assert_eq!(keys(&c), expected(&sa | &sb));
+            let mut c = a.clone();
+            c &= &b;
+            assert_eq!(keys(&c), expected(&sa & &sb));
+            assert_eq!(c.validate(), Ok(()));
+            let mut c = a.clone();
+            c -= &b;
+            assert_eq!(keys(&c), expected(&sa - &sb));
+            assert_eq!(c.validate(), Ok(()));
+        }
+    }
+}
+
+#[test]
+fn operands_are_unchanged() {
+    let a: RedBlackTree<i32> = (0..10).collect();
+    let b: RedBlackTree<i32> = (5..15).collect();
+    let _ = (&a | &b, &a & &b, &a - &b, &a ^ &b);
+    assert_eq!(keys(&a), (0..10).collect::<Vec<_>>());
+    assert_eq!(keys(&b), (5..15).collect::<Vec<_>>());
+    // 和自己运算
+    let mut c = a.clone();
+    c -= &a;
+    assert!(c.is_empty());
+    assert!((&a ^ &a).is_empty());
+    assert_eq!(&a & &a, a);
+}
+
+#[test]
+fn result_keeps_the_comparator() {
+    let a: RedBlackTree<i32, Reverse<Natural>> = (0..5).collect();
+    let b: RedBlackTree<i32, Reverse<Natural>> = (3..8).collect();
+    assert_eq!(keys(&(&a | &b)), [7, 6, 5, 4, 3, 2, 1, 0]);
+    assert_eq!(keys(&(&a ^ &b)), [7, 6, 5, 2, 1, 0]);
+}
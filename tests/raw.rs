@@ -0,0 +1,138 @@
+// 用到了 fuzz，需要 std
+#![cfg(feature = "std")]
+
+mod common;
+
+use std::alloc::Layout;
+use std::cell::Cell;
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+use common::{check_drops, check_seed, Tracked};
+use rb_tree::fuzz::{gen_ops, Config, Op};
+use rb_tree::rbtree_mod::{Global, Natural, NodeAlloc, RawRedBlackTree, RedBlackTree};
+
+// Miri 很慢，用小一些的规模
+const SEEDS: u64 = if cfg!(miri) { 3 } else { 100 };
+const STEPS: usize = if cfg!(miri) { 300 } else { 2000 };
+
+#[test]
+fn differential() {
+    for seed in 0..SEEDS {
+        // 用 String 做键，Miri 能发现重复释放和泄漏
+        check_seed(&mut RawRedBlackTree::new(), seed, Config { steps: STEPS, keys: 32 }, |k| k.to_string());
+    }
+}
+
+#[test]
+fn same_order_as_rc_tree() {
+    let keys = gen_ops(9, Config { steps: STEPS, keys: 1000 });
+    let mut raw = RawRedBlackTree::new();
+    let mut rc = RedBlackTree::new();
+    for op in keys {
+        match op {
+            Op::Add(k) => {
+                raw.add(k);
+                rc.add(k);
+            },
+            Op::Del(k) => {
+                raw.del(&k);
+                rc.del(&k);
+            },
+            _ => {}
+        }
+    }
+    assert_eq!(raw.len(), rc.len());
+    assert!(raw.iter().copied().eq(rc.iter().map(|x| *x.as_ref().borrow().value())));
+    assert_eq!(raw.range(100..200).count(), rc.range(100..200).count());
+    assert_eq!(raw.floor(&500), rc.floor(&500).map(|x| *x.as_ref().borrow().value()).as_ref());
+    assert_eq!(raw.ceiling(&500), rc.ceiling(&500).map(|x| *x.as_ref().borrow().value()).as_ref());
+}
+
+#[test]
+fn every_value_dropped_once() {
+    let live = Rc::new(Cell::new(0));
+    let n = STEPS as i32 / 2;
+    let mut tree = RawRedBlackTree::new();
+    for k in 0..n {
+        tree.add(Tracked::new(k, &live));
+    }
+    // 替换相同的键时旧值被释放
+    tree.add(Tracked::new(0, &live));
+    assert_eq!(live.get(), n);
+    let taken = tree.take(&Tracked::new(1, &live));
+    assert_eq!(live.get(), n);
+    drop(taken);
+    assert_eq!(live.get(), n - 1);
+    for k in (0..n).step_by(3) {
+        tree.del(&Tracked::new(k, &live));
+    }
+    assert_eq!(live.get() as usize, tree.len());
+    let copy = tree.clone();
+    tree.clear();
+    assert_eq!(tree.validate(), Ok(()));
+    assert_eq!(live.get() as usize, copy.len());
+    drop(copy);
+    assert_eq!(live.get(), 0);
+    check_drops(RawRedBlackTree::new(), n);
+}
+
+#[test]
+fn drop_deep_tree() {
+    let n = if cfg!(miri) { 2000 } else { 1_000_000 };
+    let tree: RawRedBlackTree<u32> = (0..n).collect();
+    assert_eq!(tree.len(), n as usize);
+    assert_eq!((tree.first(), tree.last()), (Some(&0), Some(&(n - 1))));
+    drop(tree);
+}
+
+#[test]
+fn send_to_thread() {
+    let tree: RawRedBlackTree<String> = ["b", "a", "c"].iter().map(|s| s.to_string()).collect();
+    let handle = std::thread::spawn(move || format!("{:?}", tree));
+    assert_eq!(handle.join().unwrap(), r#"{"a", "b", "c"}"#);
+}
+
+// 转发给 Global，记录分配次数和还没释放的节点
+#[derive(Default)]
+struct CountingAlloc {
+    allocs: usize,
+    live: usize,
+    layout: Option<Layout>
+}
+
+unsafe impl NodeAlloc for CountingAlloc {
+    fn allocate(&mut self, layout:Layout) -> Option<NonNull<u8>> {
+        assert_eq!(*self.layout.get_or_insert(layout), layout, "all nodes share one layout");
+        self.allocs += 1;
+        self.live += 1;
+        Global.allocate(layout)
+    }
+    unsafe fn deallocate(&mut self, ptr:NonNull<u8>, layout:Layout) {
+        self.live -= 1;
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn custom_allocator() {
+    let mut counting = CountingAlloc::default();
+    let mut tree = RawRedBlackTree::new_in(&mut counting);
+    for k in 0..100 {
+        tree.add(k.to_string());
+    }
+    // 替换已有的键不分配
+    tree.add("7".to_string());
+    assert_eq!((tree.allocator().allocs, tree.allocator().live), (100, 100));
+    for k in 0..50 {
+        assert!(tree.remove(&k.to_string()));
+    }
+    assert_eq!(tree.allocator().live, 50);
+    assert_eq!(tree.validate(), Ok(()));
+    drop(tree);
+    assert_eq!(counting.live, 0);
+
+    let mut tree: RawRedBlackTree<i32, Natural, CountingAlloc> = (0..10).collect();
+    tree.clear();
+    assert_eq!((tree.allocator().allocs, tree.allocator().live), (10, 0));
+}
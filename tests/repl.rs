@@ -0,0 +1,86 @@
+// 二进制需要 std
+#![cfg(feature = "std")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// 把脚本送进 stdin，返回 stdout
+fn run(script:&str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rb_tree"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(script.as_bytes()).unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    String::from_utf8(out.stdout).unwrap()
+}
+
+#[test]
+fn scripted_session() {
+    let out = run("\
+# 注释行被跳过
+add 5 3 8 1
+get 3
+get 4
+range 2 6
+del 3 9
+range 0 100
+validate
+undo
+range 0 100
+undo
+range 0 100
+undo
+");
+    assert_eq!(out, "\
+3 Black
+4 not found
+[3, 5]
+9 not found
+[1, 5, 8]
+ok
+[1, 3, 5, 8]
+[]
+nothing to undo
+");
+}
+
+#[test]
+fn print_dot_and_show() {
+    let out = run("add 2 1 3\nprint\ndot\n");
+    assert!(out.starts_with("    3[R]\n2[B]\n    1[R]\n"));
+    assert!(out.contains("digraph rbtree {"));
+    // 打开 show 后每次修改都打印树，clear 之后为空
+    let out = run("show on\nadd 1\nclear\nshow off\nadd 2\n");
+    assert_eq!(out, "1[B]\n(empty)\n");
+}
+
+#[test]
+fn stats_and_memory() {
+    let out = run("add 1 2 3 4 5 6 7\nstats\nmemory\n");
+    assert!(out.starts_with("len:            7\n"));
+    assert!(out.contains("black height:"));
+    assert!(out.contains("depth histogram:"));
+}
+
+#[test]
+fn errors_and_quit() {
+    let out = run("add x\ndel\nrange 1\nfrobnicate 3\nhelp\nquit\nadd 1\nprint\n");
+    let lines: Vec<&str> = out.lines().collect();
+    assert!(lines[0].starts_with("error: bad key \"x\""));
+    assert_eq!(lines[1], "error: missing key");
+    assert_eq!(lines[2], "unknown command: range 1 (try help)");
+    assert_eq!(lines[3], "unknown command: frobnicate 3 (try help)");
+    assert_eq!(lines[4], "commands:");
+    // quit 之后的命令不执行
+    assert!(!out.contains("[B]"));
+}
+
+// 没有改动树的命令不进入 undo 历史
+#[test]
+fn undo_skips_no_ops() {
+    let out = run("add 1 2\nadd 2\ndel 7\nclear\nclear\nundo\nrange 0 9\nundo\nrange 0 9\nundo\n");
+    assert_eq!(out, "7 not found\n[1, 2]\n[]\nnothing to undo\n");
+}
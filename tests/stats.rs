@@ -0,0 +1,58 @@
+use rb_tree::rbtree_mod::{RedBlackTree, TreeStats};
+
+#[test]
+fn stats_of_a_known_tree() {
+    // 2B(1B, 5R(4B(3R, -), 6B(-, 7R)))
+    let tree: RedBlackTree<i32> = [4, 2, 6, 1, 3, 5, 7].into_iter().collect();
+    let stats = tree.stats();
+    assert_eq!(stats, TreeStats {
+        len: 7,
+        height: 4,
+        black_height: 2,
+        red_count: 3,
+        black_count: 4,
+        leaf_count: 3,
+        avg_leaf_depth: 7.0 / 3.0,
+        max_leaf_depth: 3,
+        depth_histogram: vec![1, 2, 2, 2]
+    });
+    assert_eq!(stats.to_string(), "\
+len:            7
+height:         4
+black height:   2
+red / black:    3 / 4
+leaves:         3
+leaf depth:     avg 2.33, max 3
+depth histogram:
+    0: 1
+    1: 2
+    2: 2
+    3: 2");
+}
+
+#[test]
+fn empty_tree() {
+    let tree: RedBlackTree<i32> = RedBlackTree::new();
+    assert_eq!(tree.stats(), TreeStats::default());
+}
+
+#[test]
+fn bounds_hold_on_large_trees() {
+    for n in [2, 3, 10, 1000, 20000] {
+        let mut tree: RedBlackTree<i32> = (0..n).collect();
+        for k in (0..n).step_by(5) {
+            tree.del(&k);
+        }
+        let s = tree.stats();
+        assert_eq!(s.len, tree.len());
+        assert_eq!(s.red_count + s.black_count, s.len);
+        assert_eq!(s.depth_histogram.iter().sum::<usize>(), s.len);
+        assert_eq!(s.height, s.max_leaf_depth + 1);
+        // 红黑树的高度不超过 2 log2(n + 1)，也不超过两倍黑高
+        assert!(s.height as f64 <= 2.0 * ((s.len + 1) as f64).log2());
+        assert!(s.height <= 2 * s.black_height);
+        assert!(s.avg_leaf_depth <= s.max_leaf_depth as f64);
+        // 复制保持形状，统计相同
+        assert_eq!(tree.clone().stats(), s);
+    }
+}
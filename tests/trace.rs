@@ -0,0 +1,72 @@
+use rb_tree::rbtree_mod::{Color, Frame, RedBlackTree, Rotation, TraceEvent, TraceStep};
+
+fn labels(steps:&[TraceStep]) -> Vec<&str> {
+    steps.iter().map(|s| s.label.as_str()).collect()
+}
+
+#[test]
+fn frame_renders_ascii_and_dot() {
+    let mut tree = RedBlackTree::new();
+    assert_eq!(tree.frame(), Frame::default());
+    assert_eq!(tree.frame().to_ascii(), "(empty)\n");
+    for k in [2, 1, 3] {
+        tree.add(k);
+    }
+    let frame = tree.frame();
+    assert_eq!(frame.nodes[0].key, "2");
+    assert_eq!(frame.nodes[0].color, Color::Black);
+    // 右子树在上
+    assert_eq!(frame.to_ascii(), "    3[R]\n2[B]\n    1[R]\n");
+    let dot = frame.to_dot();
+    assert!(dot.starts_with("digraph rbtree {"));
+    assert!(dot.contains("n0 [label=\"2\", fillcolor=black];"));
+    assert_eq!(dot.matches("shape=point").count(), 4);
+}
+
+#[test]
+fn add_records_rotations() {
+    let mut tree = RedBlackTree::new();
+    tree.add(1);
+    tree.add(2);
+    assert!(!tree.is_tracing());
+    tree.start_trace();
+    assert!(tree.is_tracing());
+    tree.add(3);
+    let trace = tree.take_trace().unwrap();
+    assert!(!tree.is_tracing());
+    assert_eq!(trace.steps[0].label, "add 3");
+    let events: Vec<_> = trace.steps.iter().flat_map(|s| s.events.iter().cloned()).collect();
+    assert!(events.contains(&TraceEvent::Rotate { kind: Rotation::Left, pivot: "2".to_string() }));
+    // 最后一步定格在 add 结束之后
+    assert_eq!(trace.steps.last().unwrap().frame, tree.frame());
+    assert_eq!(trace.render_dot().len(), trace.steps.len());
+    assert!(trace.render_ascii().starts_with("== 0 add 3\n"));
+}
+
+#[test]
+fn del_starts_with_the_tree_before_deletion() {
+    let mut tree: RedBlackTree<i32> = (0..15).collect();
+    let before = tree.frame();
+    tree.start_trace();
+    // 7 有两个子节点，需要先和后继交换
+    tree.del(&7);
+    let trace = tree.take_trace().unwrap();
+    assert_eq!(labels(&trace.steps)[..2], ["del 7", "del 与后继交换"]);
+    assert_eq!(trace.steps[0].frame, before);
+    assert!(trace.steps[0].events.is_empty());
+    assert_eq!(trace.steps[1].events[0], TraceEvent::Swap { a: "7".to_string(), b: "8".to_string() });
+    assert!(trace.steps.iter().flat_map(|s| &s.events).any(|e| *e == TraceEvent::Remove { key: "7".to_string() }));
+    assert_eq!(trace.steps.last().unwrap().frame, tree.frame());
+}
+
+#[test]
+fn del_of_leaf_has_no_swap_step() {
+    let mut tree: RedBlackTree<i32> = (0..15).collect();
+    let before = tree.frame();
+    tree.start_trace();
+    tree.del(&14);
+    let trace = tree.take_trace().unwrap();
+    assert_eq!(trace.steps[0].label, "del 14");
+    assert_eq!(trace.steps[0].frame, before);
+    assert!(!labels(&trace.steps).contains(&"del 与后继交换"));
+}
@@ -0,0 +1,82 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::hash::{Hash, Hasher};
+
+use rb_tree::rbtree_mod::{Natural, RedBlackTree};
+
+fn hash_of(x:&impl Hash) -> u64 {
+    let mut h = DefaultHasher::new();
+    x.hash(&mut h);
+    h.finish()
+}
+
+#[test]
+fn default_from_iter_and_extend() {
+    let empty: RedBlackTree<i32> = RedBlackTree::default();
+    assert!(empty.is_empty());
+    let mut tree: RedBlackTree<i32> = [3, 1, 2, 3].into_iter().collect();
+    assert_eq!(tree.len(), 3);
+    tree.extend(vec![5, 4]);
+    tree.extend(&[0, 1]);
+    assert_eq!(format!("{:?}", tree), "{0, 1, 2, 3, 4, 5}");
+    assert_eq!(format!("{:?}", empty), "{}");
+    assert_eq!(tree.validate(), Ok(()));
+}
+
+#[test]
+fn equality_ignores_shape() {
+    // 插入顺序不同，形状不同，内容相同
+    let a: RedBlackTree<i32> = (0..100).collect();
+    let b: RedBlackTree<i32> = (0..100).rev().collect();
+    assert_ne!(a.frame(), b.frame());
+    assert_eq!(a, b);
+    assert_eq!(hash_of(&a), hash_of(&b));
+    let mut c = b.clone();
+    c.del(&50);
+    assert_ne!(a, c);
+    c.add(50);
+    assert_eq!(a, c);
+    assert_eq!(hash_of(&a), hash_of(&c));
+}
+
+#[test]
+fn ordering_matches_btreeset() {
+    let sets: Vec<Vec<i32>> = vec![vec![], vec![1], vec![1, 2], vec![1, 3], vec![2], vec![0, 5, 9], vec![1, 2, 3]];
+    for x in &sets {
+        for y in &sets {
+            let (tx, ty): (RedBlackTree<i32>, RedBlackTree<i32>) = (x.iter().copied().collect(), y.iter().copied().collect());
+            let (bx, by): (BTreeSet<i32>, BTreeSet<i32>) = (x.iter().copied().collect(), y.iter().copied().collect());
+            assert_eq!(tx.cmp(&ty), bx.cmp(&by), "{:?} vs {:?}", x, y);
+            assert_eq!(tx.partial_cmp(&ty), bx.partial_cmp(&by), "{:?} vs {:?}", x, y);
+            assert_eq!(tx == ty, bx == by);
+        }
+    }
+}
+
+// f64 只有 PartialOrd，用 total_cmp 排序
+type FloatTree = RedBlackTree<f64, fn(&f64, &f64) -> Ordering>;
+
+#[test]
+fn partial_ord_keys() {
+    let a = FloatTree::with_comparator(f64::total_cmp);
+    let mut b = a.clone();
+    b.add(f64::NAN);
+    let mut c = a.clone();
+    c.add(1.0);
+    assert_eq!(a.partial_cmp(&b), Some(Ordering::Less));
+    // NaN 和其他值无法比较
+    assert_eq!(b.partial_cmp(&c), None);
+    assert_ne!(b, b.clone());
+}
+
+#[test]
+fn trees_in_a_tree() {
+    // Ord 让树本身可以做键
+    let mut outer: RedBlackTree<RedBlackTree<i32>, Natural> = RedBlackTree::new();
+    outer.add((0..3).collect());
+    outer.add([2, 1, 0].into_iter().collect());
+    outer.add((1..2).collect());
+    assert_eq!(outer.len(), 2);
+    assert_eq!(format!("{:?}", outer), "{{0, 1, 2}, {1}}");
+}
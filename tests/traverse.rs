@@ -0,0 +1,71 @@
+use rb_tree::rbtree_mod::{Color, NodeView, RedBlackTree, Side};
+
+fn summary(views:impl Iterator<Item = NodeView<i32>>) -> Vec<(i32, Color, usize, Side)> {
+    views.map(|v| (*v.value(), v.color, v.depth, v.side)).collect()
+}
+
+fn keys(views:impl Iterator<Item = NodeView<i32>>) -> Vec<i32> {
+    views.map(|v| *v.value()).collect()
+}
+
+// 插入后为 2B(1B, 5R(4B(3R, -), 6B(-, 7R)))
+fn seven() -> RedBlackTree<i32> {
+    [4, 2, 6, 1, 3, 5, 7].into_iter().collect()
+}
+
+#[test]
+fn orders_of_a_known_tree() {
+    let tree = seven();
+    assert_eq!(keys(tree.iter_preorder()), [2, 1, 5, 4, 3, 6, 7]);
+    assert_eq!(keys(tree.iter_postorder()), [1, 3, 4, 7, 6, 5, 2]);
+    assert_eq!(keys(tree.iter_level_order()), [2, 1, 5, 4, 6, 3, 7]);
+    use Color::*;
+    assert_eq!(summary(tree.iter_preorder()), [
+        (2, Black, 0, Side::Root),
+        (1, Black, 1, Side::Left),
+        (5, Red, 1, Side::Right),
+        (4, Black, 2, Side::Left),
+        (3, Red, 3, Side::Left),
+        (6, Black, 2, Side::Right),
+        (7, Red, 3, Side::Right)
+    ]);
+}
+
+#[test]
+fn empty_and_single() {
+    let mut tree = RedBlackTree::new();
+    assert_eq!(tree.iter_preorder().count(), 0);
+    assert_eq!(tree.iter_postorder().count(), 0);
+    assert_eq!(tree.iter_level_order().count(), 0);
+    tree.add(1);
+    assert_eq!(summary(tree.iter_level_order()), [(1, Color::Black, 0, Side::Root)]);
+}
+
+#[test]
+fn orders_agree_on_a_large_tree() {
+    let mut tree: RedBlackTree<i32> = (0..1000).map(|k| k * 7919 % 1000).collect();
+    for k in (0..1000).step_by(3) {
+        tree.del(&k);
+    }
+    let pre = summary(tree.iter_preorder());
+    let post = summary(tree.iter_postorder());
+    let level = summary(tree.iter_level_order());
+    assert_eq!((pre.len(), post.len(), level.len()), (tree.len(), tree.len(), tree.len()));
+    // 三种顺序是同一组节点
+    let mut sorted = [pre.clone(), post.clone(), level.clone()];
+    for s in sorted.iter_mut() {
+        s.sort_by_key(|x| x.0);
+    }
+    assert!(sorted[0] == sorted[1] && sorted[1] == sorted[2]);
+    assert!(sorted[0].iter().map(|x| x.0).eq(tree.iter().map(|x| *x.as_ref().borrow().value())));
+    // 先序以根开始，后序以根结束，层序深度不减
+    assert_eq!(pre[0].3, Side::Root);
+    assert_eq!(post.last(), pre.first());
+    assert!(level.windows(2).all(|w| w[0].2 <= w[1].2));
+    // 先序中每个节点的父节点是它前面最近的浅一层的节点，红节点的父节点是黑的
+    for (i, &(k, color, depth, side)) in pre.iter().enumerate().skip(1) {
+        let parent = pre[..i].iter().rev().find(|x| x.2 + 1 == depth).unwrap();
+        assert_eq!(side == Side::Left, k < parent.0);
+        assert!(color == Color::Black || parent.1 == Color::Black);
+    }
+}